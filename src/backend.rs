@@ -0,0 +1,63 @@
+use std::future::Future;
+
+use super::Client;
+use super::ExecResponse;
+use super::Executor;
+use super::PistonError;
+use super::Runtime;
+
+/// Something that can fetch runtimes and execute code, like Piston.
+///
+/// Accepting `impl PistonBackend` instead of a [`Client`] lets you
+/// swap in the `MockClient` from the `mock` feature when testing code
+/// that talks to Piston.
+///
+/// # Example
+/// ```
+/// use piston_rs::PistonBackend;
+///
+/// async fn run(backend: &impl PistonBackend, code: &str) -> String {
+///     let executor = piston_rs::Executor::new()
+///         .set_language("python")
+///         .add_file(piston_rs::File::default().set_content(code));
+///
+///     match backend.execute(&executor).await {
+///         Ok(response) => response.run.output,
+///         Err(e) => e.to_string(),
+///     }
+/// }
+/// ```
+pub trait PistonBackend {
+    /// Fetches the available runtimes.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    fn fetch_runtimes(&self) -> impl Future<Output = Result<Vec<Runtime>, PistonError>> + Send;
+
+    /// Executes code using a given executor.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response or the
+    ///   error, if any.
+    fn execute(
+        &self,
+        executor: &Executor,
+    ) -> impl Future<Output = Result<ExecResponse, PistonError>> + Send;
+}
+
+impl PistonBackend for Client {
+    fn fetch_runtimes(&self) -> impl Future<Output = Result<Vec<Runtime>, PistonError>> + Send {
+        Client::fetch_runtimes(self)
+    }
+
+    fn execute(
+        &self,
+        executor: &Executor,
+    ) -> impl Future<Output = Result<ExecResponse, PistonError>> + Send {
+        Client::execute(self, executor)
+    }
+}
@@ -0,0 +1,272 @@
+//! A blocking client, for use in synchronous code.
+//!
+//! Requires the `blocking` feature to be enabled.
+//!
+//! ##### Make requests to Piston
+//!
+//! ```no_run
+//! let client = piston_rs::blocking::Client::new();
+//! let executor = piston_rs::Executor::new()
+//!     .set_language("rust")
+//!     .set_version("*")
+//!     .add_file(
+//!         piston_rs::File::default()
+//!             .set_name("main.rs")
+//!             .set_content("fn main() { println!(\"42\"); }")
+//!     );
+//!
+//! match client.execute(&executor) {
+//!     Ok(response) => println!("Output: {}", response.run.output),
+//!     Err(e) => println!("Something went wrong contacting Piston: {}", e),
+//! }
+//! ```
+
+use std::time::Instant;
+
+use reqwest::header::HeaderMap;
+
+use super::client::DEFAULT_URL;
+use super::ExecResponse;
+use super::Executor;
+use super::PistonError;
+use super::Runtime;
+
+/// A blocking client used to send requests to Piston.
+///
+/// This mirrors the async [`crate::Client`], but its methods block the
+/// current thread until Piston responds. It must not be used from
+/// within an async runtime.
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// The base url for Piston.
+    url: String,
+    /// The blocking reqwest client to use.
+    client: reqwest::blocking::Client,
+    /// The headers to send with each request.
+    headers: HeaderMap,
+}
+
+impl Default for Client {
+    /// Creates a new client. Alias for [`Client::new`].
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::blocking::Client::default();
+    ///
+    /// assert!(!client.get_headers().contains_key("Authorization"));
+    /// assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston".to_string());
+    /// ```
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// Creates a new client.
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::blocking::Client::new();
+    ///
+    /// assert!(client.get_headers().contains_key("Accept"));
+    /// assert!(client.get_headers().contains_key("User-Agent"));
+    /// assert!(!client.get_headers().contains_key("Authorization"));
+    /// ```
+    pub fn new() -> Self {
+        Self::with_reqwest_client(reqwest::blocking::Client::new(), DEFAULT_URL, None)
+    }
+
+    /// Creates a new Client with a url that runs the piston code
+    /// execution engine.
+    ///
+    /// # Arguments
+    /// - `url` - The url to use as the underlying piston backend.
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::blocking::Client::with_url("http://localhost:3000");
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// ```
+    pub fn with_url(url: &str) -> Self {
+        Self::with_reqwest_client(reqwest::blocking::Client::new(), url, None)
+    }
+
+    /// Creates a new client, with an api key.
+    ///
+    /// # Arguments
+    /// - `key` - The api key to use.
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::blocking::Client::with_key("123abc");
+    ///
+    /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    /// ```
+    pub fn with_key(key: &str) -> Self {
+        Self::with_reqwest_client(reqwest::blocking::Client::new(), DEFAULT_URL, Some(key))
+    }
+
+    /// Creates a new Client using a url and an api key.
+    ///
+    /// # Arguments
+    /// - `url` - The url to use as the underlying piston backend.
+    /// - `key` - The api key to use.
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::blocking::Client::with_url_and_key(
+    ///     "http://localhost:3000",
+    ///     "123abc",
+    /// );
+    ///
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    /// ```
+    pub fn with_url_and_key(url: &str, key: &str) -> Self {
+        Self::with_reqwest_client(reqwest::blocking::Client::new(), url, Some(key))
+    }
+
+    /// Creates a new Client using an existing blocking reqwest client.
+    ///
+    /// # Arguments
+    /// - `client` - The blocking reqwest client to use.
+    /// - `url` - The url to use as the underlying piston backend.
+    /// - `key` - The optional api key to use.
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::blocking::Client::with_reqwest_client(
+    ///     reqwest::blocking::Client::new(),
+    ///     "http://localhost:3000",
+    ///     None,
+    /// );
+    ///
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// ```
+    pub fn with_reqwest_client(
+        client: reqwest::blocking::Client,
+        url: &str,
+        key: Option<&str>,
+    ) -> Self {
+        Self {
+            url: crate::client::normalize_url(url),
+            client,
+            headers: crate::Client::generate_headers(key),
+        }
+    }
+
+    /// The base url for the Piston V2 API that is being used by this
+    /// client.
+    ///
+    /// # Returns
+    /// - [`String`] - The requested url.
+    pub fn get_url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// The headers being used by this client.
+    ///
+    /// # Returns
+    /// - [`HeaderMap`] - A map of Header key, value pairs.
+    pub fn get_headers(&self) -> HeaderMap {
+        self.headers.clone()
+    }
+
+    /// Fetches the runtimes from Piston. **This is a blocking http
+    /// request**.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let client = piston_rs::blocking::Client::new();
+    ///
+    /// if let Ok(runtimes) = client.fetch_runtimes() {
+    ///     assert!(!runtimes.is_empty());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// ```
+    pub fn fetch_runtimes(&self) -> Result<Vec<Runtime>, PistonError> {
+        let endpoint = format!("{}/runtimes", self.url);
+        let data = self
+            .client
+            .get(endpoint)
+            .headers(self.headers.clone())
+            .send()?;
+
+        let status = data.status();
+        let headers = data.headers().clone();
+        let text = data.text()?;
+
+        crate::Client::parse_runtimes(status, &headers, text)
+    }
+
+    /// Executes code using a given executor. **This is a blocking http
+    /// request**.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let client = piston_rs::blocking::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// if let Ok(response) = client.execute(&executor) {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// ```
+    pub fn execute(&self, executor: &Executor) -> Result<ExecResponse, PistonError> {
+        executor.validate()?;
+
+        let endpoint = format!("{}/execute", self.url);
+        let started = Instant::now();
+        let timed_out =
+            |e: reqwest::Error| PistonError::Http(e).with_timeout(&endpoint, started.elapsed());
+        let data = self
+            .client
+            .post(&endpoint)
+            .headers(self.headers.clone())
+            .json::<Executor>(executor)
+            .send()
+            .map_err(timed_out)?;
+
+        let status = data.status();
+        let headers = data.headers().clone();
+        let text = data.text().map_err(timed_out)?;
+
+        let mut response = crate::Client::parse_execute(status, &headers, text)
+            .map_err(|e| e.with_language(&executor.language))?;
+        response.elapsed = Some(started.elapsed());
+        Ok(response)
+    }
+}
@@ -1,12 +1,32 @@
-use std::error::Error;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use reqwest::header::{HeaderMap, HeaderValue};
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, LINK};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
 
 use super::executor::RawExecResponse;
 use super::ExecResponse;
-use super::ExecResult;
 use super::Executor;
+use super::File;
+use super::Package;
+use super::PistonError;
 use super::Runtime;
+use super::Signal;
+
+/// The url of the public Piston instance hosted by emkc.
+pub(crate) const DEFAULT_URL: &str = "https://emkc.org/api/v2/piston";
+
+/// The maximum number of bytes of file content in each chunk of a
+/// body streamed by [`Client::execute_large`].
+const LARGE_BODY_CHUNK_LEN: usize = 64 * 1024;
+
+/// The delay before the first retry when Piston does not send a
+/// `Retry-After` header. Doubled for each subsequent retry.
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(250);
 
 /// A client used to send requests to Piston.
 #[derive(Debug, Clone)]
@@ -17,6 +37,270 @@ pub struct Client {
     client: reqwest::Client,
     /// The headers to send with each request.
     headers: HeaderMap,
+    /// The header the api key is sent in, or [`None`] if basic auth
+    /// uses `Authorization` and no other header was set for the key.
+    key_header: Option<HeaderName>,
+    /// The runtime cache, if caching is enabled.
+    runtime_cache: Option<Arc<Mutex<RuntimeCache>>>,
+    /// The default timeout for each request, if any.
+    timeout: Option<Duration>,
+    /// The rate limit Piston reported in its latest response, and
+    /// when it was reported.
+    rate_limit: Arc<Mutex<Option<(RateLimit, Instant)>>>,
+    /// The timeout for fetching runtimes, if not the default timeout.
+    runtime_fetch_timeout: Option<Duration>,
+    /// The maximum size of a response body, if any.
+    max_output_bytes: Option<usize>,
+    /// Whether or not to add random jitter to retry delays.
+    retry_jitter: bool,
+}
+
+/// How [`Client::execute_with_policy`] retries failed requests.
+///
+/// Rate limited requests (HTTP 429) are always retried. By default,
+/// responses with a 502, 503 or 504 status and failed connections are
+/// retried too, up to 3 times, backing off exponentially from 250ms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of times to retry.
+    max_retries: u32,
+    /// The status codes to retry, other than 429.
+    statuses: Vec<u16>,
+    /// Whether or not to retry when the connection to Piston fails.
+    retry_connection_errors: bool,
+    /// The delay before the first retry, doubled for each retry after.
+    base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Creates a new retry policy. Alias for [`RetryPolicy::new`].
+    ///
+    /// # Returns
+    /// - [`RetryPolicy`] - The new policy.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new retry policy, with the defaults described on
+    /// [`RetryPolicy`].
+    ///
+    /// # Returns
+    /// - [`RetryPolicy`] - The new policy.
+    ///
+    /// # Example
+    /// ```
+    /// let policy = piston_rs::RetryPolicy::new();
+    ///
+    /// assert!(policy.retries_status(503));
+    /// assert!(!policy.retries_status(500));
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            max_retries: 3,
+            statuses: vec![502, 503, 504],
+            retry_connection_errors: true,
+            base_delay: BASE_RETRY_DELAY,
+        }
+    }
+
+    /// Sets the maximum number of times to retry.
+    ///
+    /// # Arguments
+    /// - `max_retries` - The maximum number of retries.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the status codes to retry, replacing the defaults. Rate
+    /// limited requests are retried regardless.
+    ///
+    /// # Arguments
+    /// - `statuses` - The status codes to retry.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let policy = piston_rs::RetryPolicy::new().statuses(&[500, 503]);
+    ///
+    /// assert!(policy.retries_status(500));
+    /// assert!(!policy.retries_status(502));
+    /// ```
+    #[must_use]
+    pub fn statuses(mut self, statuses: &[u16]) -> Self {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    /// Sets whether or not to retry when the connection to Piston
+    /// fails, or is dropped before the response is received.
+    ///
+    /// # Arguments
+    /// - `retry` - Whether or not to retry connection errors.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn retry_connection_errors(mut self, retry: bool) -> Self {
+        self.retry_connection_errors = retry;
+        self
+    }
+
+    /// Sets the delay before the first retry, which is doubled for
+    /// each retry after. Piston's `Retry-After` header takes precedence
+    /// for rate limited requests.
+    ///
+    /// # Arguments
+    /// - `delay` - The delay before the first retry.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Whether or not responses with the given status are retried.
+    ///
+    /// # Arguments
+    /// - `status` - The status code.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the status is retried.
+    pub fn retries_status(&self, status: u16) -> bool {
+        status == 429 || self.statuses.contains(&status)
+    }
+
+    /// Whether or not the error should be retried under this policy.
+    ///
+    /// # Arguments
+    /// - `error` - The error to check.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the request should be retried.
+    fn retries(&self, error: &PistonError) -> bool {
+        match error {
+            PistonError::RateLimited { .. } => true,
+            PistonError::Api { status, .. } => self.retries_status(*status),
+            PistonError::Http(e) => {
+                self.retry_connection_errors && (e.is_connect() || e.is_request() || e.is_body())
+            }
+            _ => false,
+        }
+    }
+
+    /// Calculates the exponential backoff delay for a retry.
+    ///
+    /// # Arguments
+    /// - `attempt` - The number of retries made so far.
+    ///
+    /// # Returns
+    /// - [`Duration`] - How long to wait before retrying.
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(attempt))
+    }
+}
+
+/// The rate limit Piston reported in the `X-RateLimit-*` headers of a
+/// response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum number of requests allowed in the current window,
+    /// from `X-RateLimit-Limit`.
+    pub limit: Option<u64>,
+    /// The number of requests left in the current window, from
+    /// `X-RateLimit-Remaining`.
+    pub remaining: Option<u64>,
+    /// When the current window resets, from `X-RateLimit-Reset`, as
+    /// sent by Piston.
+    pub reset: Option<u64>,
+}
+
+impl RateLimit {
+    /// Parses the rate limit from the headers of a response.
+    ///
+    /// # Arguments
+    /// - `headers` - The headers of the response.
+    ///
+    /// # Returns
+    /// - [`Option<RateLimit>`] - The rate limit, if Piston sent any of
+    ///   its headers.
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let parse = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+        let rate_limit = Self {
+            limit: parse("X-RateLimit-Limit"),
+            remaining: parse("X-RateLimit-Remaining"),
+            reset: parse("X-RateLimit-Reset"),
+        };
+
+        match rate_limit {
+            Self {
+                limit: None,
+                remaining: None,
+                reset: None,
+            } => None,
+            _ => Some(rate_limit),
+        }
+    }
+
+    /// How long is left until the window this rate limit was reported
+    /// in resets.
+    ///
+    /// Resets larger than a day are taken to be unix timestamps, and
+    /// anything smaller to be the seconds left in the window. If no
+    /// reset was reported, the window is assumed to have reset.
+    ///
+    /// # Arguments
+    /// - `observed` - When the rate limit was reported.
+    ///
+    /// # Returns
+    /// - [`Duration`] - The time left, or zero if the window has reset.
+    fn until_reset(&self, observed: Instant) -> Duration {
+        const DAY: u64 = 24 * 60 * 60;
+
+        match self.reset {
+            None => Duration::ZERO,
+            Some(reset) if reset > DAY => (UNIX_EPOCH + Duration::from_secs(reset))
+                .duration_since(SystemTime::now())
+                .unwrap_or_default(),
+            Some(reset) => Duration::from_secs(reset).saturating_sub(observed.elapsed()),
+        }
+    }
+}
+
+/// Runtimes previously fetched from Piston.
+#[derive(Debug)]
+struct RuntimeCache {
+    /// How long fetched runtimes stay valid for.
+    ttl: Duration,
+    /// When the runtimes were fetched, and the runtimes themselves.
+    entry: Option<(Instant, Vec<Runtime>)>,
+}
+
+/// A copy of a client's runtime cache, taken with
+/// [`Client::export_cache`] and restored with [`ClientBuilder::with_cache`].
+///
+/// Snapshots can be serialized, so the cache can be saved when a
+/// program exits and loaded when it next starts, instead of fetching
+/// the runtimes again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheSnapshot {
+    /// How long fetched runtimes stay valid for, or [`None`] if
+    /// caching was not enabled.
+    pub ttl: Option<Duration>,
+    /// When the runtimes were fetched, if they have been.
+    pub fetched_at: Option<SystemTime>,
+    /// The cached runtimes.
+    pub runtimes: Vec<Runtime>,
 }
 
 impl Default for Client {
@@ -40,6 +324,23 @@ impl Default for Client {
 }
 
 impl Client {
+    /// Creates a [`ClientBuilder`] for configuring a new client.
+    ///
+    /// # Returns
+    /// - [`ClientBuilder`] - The new builder.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::builder()
+    ///     .url("http://localhost:3000")
+    ///     .build();
+    ///
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// ```
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
     /// Creates a new client.
     ///
     /// # Returns
@@ -54,11 +355,7 @@ impl Client {
     /// assert!(!client.get_headers().contains_key("Authorization"));
     /// ```
     pub fn new() -> Self {
-        Self {
-            url: "https://emkc.org/api/v2/piston".to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(None),
-        }
+        Self::with_reqwest_client(reqwest::Client::new(), DEFAULT_URL, None)
     }
 
     /// Creates a new Client with a url that runs the piston code execution engine.
@@ -77,11 +374,7 @@ impl Client {
     /// assert_eq!(client.get_url(), "http://localhost:3000");
     /// ```
     pub fn with_url(url: &str) -> Self {
-        Self {
-            url: url.to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(None),
-        }
+        Self::with_reqwest_client(reqwest::Client::new(), url, None)
     }
 
     /// Creates a new client, with an api key.
@@ -100,11 +393,72 @@ impl Client {
     /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
     /// ```
     pub fn with_key(key: &str) -> Self {
-        Self {
-            url: "https://emkc.org/api/v2/piston".to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(Some(key)),
+        Self::with_reqwest_client(reqwest::Client::new(), DEFAULT_URL, Some(key))
+    }
+
+    /// Creates a new client configured from environment variables.
+    ///
+    /// - `PISTON_URL` - The url to use as the underlying piston
+    ///   backend. Defaults to the public Piston instance.
+    /// - `PISTON_API_KEY` - The api key to use. Defaults to none.
+    ///
+    /// Variables that are unset or empty are ignored.
+    ///
+    /// # Returns
+    /// - [`Result<Client, PistonError>`] - The new Client, or a
+    ///   [`PistonError::InvalidConfig`] if a variable is malformed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let client = piston_rs::Client::from_env().unwrap();
+    /// ```
+    pub fn from_env() -> Result<Self, PistonError> {
+        let var = |name: &str| match std::env::var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(e) => Err(PistonError::InvalidConfig(format!("{}: {}", name, e))),
+        };
+
+        Self::from_vars(var("PISTON_URL")?, var("PISTON_API_KEY")?)
+    }
+
+    /// Creates a new client from the values of the environment
+    /// variables read by [`Client::from_env`].
+    ///
+    /// # Arguments
+    /// - `url` - The value of `PISTON_URL`, if set.
+    /// - `key` - The value of `PISTON_API_KEY`, if set.
+    ///
+    /// # Returns
+    /// - [`Result<Client, PistonError>`] - The new Client, or the
+    ///   error if a value is malformed.
+    fn from_vars(url: Option<String>, key: Option<String>) -> Result<Self, PistonError> {
+        let url = url.filter(|u| !u.is_empty());
+        let key = key.filter(|k| !k.is_empty());
+
+        if let Some(url) = &url {
+            let parsed = reqwest::Url::parse(url)
+                .map_err(|e| PistonError::InvalidConfig(format!("PISTON_URL: {}", e)))?;
+
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err(PistonError::InvalidConfig(format!(
+                    "PISTON_URL: unsupported scheme {}",
+                    parsed.scheme()
+                )));
+            }
+        }
+
+        if let Some(key) = &key {
+            HeaderValue::from_str(key).map_err(|_| {
+                PistonError::InvalidConfig("PISTON_API_KEY: invalid header value".to_string())
+            })?;
         }
+
+        Ok(Self::with_reqwest_client(
+            reqwest::Client::new(),
+            url.as_deref().unwrap_or(DEFAULT_URL),
+            key.as_deref(),
+        ))
     }
 
     /// Creates a new Client using a url and an api key.
@@ -124,11 +478,109 @@ impl Client {
     /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
     /// ```
     pub fn with_url_and_key(url: &str, key: &str) -> Self {
+        Self::with_reqwest_client(reqwest::Client::new(), url, Some(key))
+    }
+
+    /// Creates a new Client using an existing reqwest client.
+    ///
+    /// This makes it possible to configure connection pooling, TLS,
+    /// or anything else reqwest supports, and to share a single
+    /// reqwest client across your application.
+    ///
+    /// # Arguments
+    /// - `client` - The reqwest client to use.
+    /// - `url` - The url to use as the underlying piston backend.
+    /// - `key` - The optional api key to use.
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::with_reqwest_client(
+    ///     reqwest::Client::new(),
+    ///     "http://localhost:3000",
+    ///     Some("123abc"),
+    /// );
+    ///
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    /// ```
+    pub fn with_reqwest_client(client: reqwest::Client, url: &str, key: Option<&str>) -> Self {
         Self {
-            url: url.to_string(),
-            client: reqwest::Client::new(),
-            headers: Self::generate_headers(Some(key)),
+            url: normalize_url(url),
+            client,
+            headers: Self::generate_headers(key),
+            key_header: Some(AUTHORIZATION),
+            runtime_cache: None,
+            timeout: None,
+            rate_limit: Arc::new(Mutex::new(None)),
+            runtime_fetch_timeout: None,
+            max_output_bytes: None,
+            retry_jitter: true,
+        }
+    }
+
+    /// Enables caching of the runtimes fetched from Piston.
+    ///
+    /// Once enabled, [`Client::fetch_runtimes`] only contacts Piston
+    /// when the cached runtimes are older than `ttl`. Clones of this
+    /// client share the same cache.
+    ///
+    /// # Arguments
+    /// - `ttl` - How long fetched runtimes stay valid for.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new()
+    ///     .with_runtime_cache(Duration::from_secs(3600));
+    /// ```
+    #[must_use]
+    pub fn with_runtime_cache(mut self, ttl: Duration) -> Self {
+        self.runtime_cache = Some(Arc::new(Mutex::new(RuntimeCache { ttl, entry: None })));
+        self
+    }
+
+    /// Takes a snapshot of the runtime cache, which can be restored
+    /// with [`ClientBuilder::with_cache`].
+    ///
+    /// # Returns
+    /// - [`CacheSnapshot`] - The snapshot. It is empty if caching is
+    ///   not enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new()
+    ///     .with_runtime_cache(Duration::from_secs(3600));
+    /// let snapshot = client.export_cache();
+    ///
+    /// assert_eq!(snapshot.ttl, Some(Duration::from_secs(3600)));
+    /// assert!(snapshot.runtimes.is_empty());
+    /// ```
+    pub fn export_cache(&self) -> CacheSnapshot {
+        let mut snapshot = CacheSnapshot {
+            ttl: None,
+            fetched_at: None,
+            runtimes: vec![],
+        };
+
+        if let Some(Ok(cache)) = self.runtime_cache.as_ref().map(|c| c.lock()) {
+            snapshot.ttl = Some(cache.ttl);
+
+            if let Some((fetched_at, runtimes)) = &cache.entry {
+                snapshot.fetched_at = SystemTime::now().checked_sub(fetched_at.elapsed());
+                snapshot.runtimes = runtimes.clone();
+            }
         }
+
+        snapshot
     }
 
     /// The base url for the Piston V2 API that is being used by this client.
@@ -147,6 +599,23 @@ impl Client {
         self.url.clone()
     }
 
+    /// Sets the base url for the Piston V2 API used by this client.
+    /// Trailing slashes are removed.
+    ///
+    /// # Arguments
+    /// - `url` - The url to use as the underlying piston backend.
+    ///
+    /// # Example
+    /// ```
+    /// let mut client = piston_rs::Client::new();
+    /// client.set_url("http://localhost:3000/");
+    ///
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// ```
+    pub fn set_url(&mut self, url: &str) {
+        self.url = normalize_url(url);
+    }
+
     /// The headers being used by this client.
     ///
     /// # Returns
@@ -164,175 +633,4096 @@ impl Client {
         self.headers.clone()
     }
 
-    /// Generates the headers the client should use.
+    /// The rate limit Piston reported in the latest response that
+    /// included `X-RateLimit-*` headers. Clones of this client share
+    /// the same rate limit.
     ///
     /// # Returns
-    ///
-    /// - [`HeaderMap`] - A map of Header key, value pairs.
+    /// - [`Option<RateLimit>`] - The latest rate limit, if Piston has
+    ///   reported one.
     ///
     /// # Example
-    /// ```ignore # Fails to compile (private function)
-    /// let headers = piston_rs::Client::generate_headers(None);
+    /// ```
+    /// let client = piston_rs::Client::new();
     ///
-    /// assert!(!headers.contains_key("Authorization"));
-    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
-    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// assert!(client.last_rate_limit().is_none());
+    /// ```
+    pub fn last_rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit
+            .lock()
+            .ok()?
+            .map(|(rate_limit, _)| rate_limit)
+    }
+
+    /// Whether or not Piston is expected to accept another request,
+    /// based on the latest rate limit it reported.
     ///
-    /// let headers = piston_rs::Client::generate_headers(Some("123abc"));
+    /// This is [`false`] only when Piston reported no requests
+    /// remaining and the window has not yet reset. No request is made.
     ///
-    /// assert_eq!(headers.get("Authorization").unwrap(), "123abc");
-    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
-    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// # Returns
+    /// - [`bool`] - [`true`] if a request is not expected to be rate
+    ///   limited.
+    ///
+    /// # Example
     /// ```
-    fn generate_headers(key: Option<&str>) -> HeaderMap {
-        let mut headers = HeaderMap::with_capacity(3);
-        headers.insert("Accept", HeaderValue::from_str("application/json").unwrap());
-        headers.insert("User-Agent", HeaderValue::from_str("piston-rs").unwrap());
+    /// let client = piston_rs::Client::new();
+    ///
+    /// assert!(client.can_execute());
+    /// ```
+    pub fn can_execute(&self) -> bool {
+        self.rate_limit_wait().is_zero()
+    }
 
-        if let Some(k) = key {
-            headers.insert("Authorization", HeaderValue::from_str(k).unwrap());
+    /// How long to wait before Piston is expected to accept another
+    /// request, based on the latest rate limit it reported.
+    ///
+    /// # Returns
+    /// - [`Duration`] - The time left in the current window if Piston
+    ///   reported no requests remaining, otherwise zero.
+    fn rate_limit_wait(&self) -> Duration {
+        let latest = match self.rate_limit.lock() {
+            Ok(latest) => *latest,
+            Err(_) => return Duration::ZERO,
         };
 
-        headers
+        match latest {
+            Some((rate_limit, observed)) if rate_limit.remaining == Some(0) => {
+                rate_limit.until_reset(observed)
+            }
+            _ => Duration::ZERO,
+        }
     }
 
-    /// Fetches the runtimes from Piston. **This is an http request**.
+    /// Sets the `User-Agent` header sent by this client. Defaults to
+    /// "piston-rs".
     ///
-    /// # Returns
-    /// - [`Result<Vec<Runtime>, Box<dyn Error>>`] - The available
-    /// runtimes or the error, if any.
+    /// # Arguments
+    /// - `user_agent` - The user agent to use.
+    ///
+    /// # Panics
+    /// - If the user agent is not a valid header value.
     ///
     /// # Example
-    /// ```no_run
-    /// # #[tokio::test]
-    /// # async fn test_fetch_runtimes() {
-    /// let client = piston_rs::Client::new();
+    /// ```
+    /// let mut client = piston_rs::Client::new();
+    /// client.set_user_agent("my-bot/1.2.3");
     ///
-    /// if let Ok(runtimes) = client.fetch_runtimes().await {
-    ///     assert!(!runtimes.is_empty());
-    /// } else {
-    ///     // There was an error contacting Piston.
-    /// }
-    /// # }
+    /// assert_eq!(client.get_headers().get("User-Agent").unwrap(), "my-bot/1.2.3");
     /// ```
-    pub async fn fetch_runtimes(&self) -> Result<Vec<Runtime>, Box<dyn Error>> {
-        let endpoint = format!("{}/runtimes", self.url);
-        let runtimes = self
-            .client
-            .get(endpoint)
-            .headers(self.headers.clone())
-            .send()
-            .await?
-            .json::<Vec<Runtime>>()
-            .await?;
-
-        Ok(runtimes)
+    pub fn set_user_agent(&mut self, user_agent: &str) {
+        self.headers
+            .insert("User-Agent", HeaderValue::from_str(user_agent).unwrap());
     }
 
-    /// Executes code using a given executor. **This is an http
-    /// request**.
+    /// Sets the api key used by this client, replacing any existing
+    /// key.
+    ///
+    /// The key is sent in `Authorization`, or the header set with
+    /// [`ClientBuilder::key_header`]. If the client was built with
+    /// [`ClientBuilder::basic_auth`] and no key header, there is no
+    /// header left for the key, and this does nothing.
     ///
     /// # Arguments
-    /// - `executor` - The executor to use.
+    /// - `key` - The api key to use.
     ///
-    /// # Returns
-    /// - [`Result<ExecutorResponse, Box<dyn Error>>`] - The response
-    /// from Piston or the error, if any.
+    /// # Panics
+    /// - If the key is not a valid header value.
     ///
     /// # Example
-    /// ```no_run
+    /// ```
+    /// let mut client = piston_rs::Client::new();
+    /// client.set_key("123abc");
+    ///
+    /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    /// ```
+    pub fn set_key(&mut self, key: &str) {
+        if let Some(name) = &self.key_header {
+            let mut value = HeaderValue::from_str(key).unwrap();
+
+            // Keeps the key out of `Client::dry_run`.
+            if name != AUTHORIZATION {
+                value.set_sensitive(true);
+            }
+
+            self.headers.insert(name.clone(), value);
+        }
+    }
+
+    /// Removes the api key used by this client, if any. Basic auth
+    /// credentials are kept.
+    ///
+    /// # Example
+    /// ```
+    /// let mut client = piston_rs::Client::with_key("123abc");
+    /// client.clear_key();
+    ///
+    /// assert!(!client.get_headers().contains_key("Authorization"));
+    /// ```
+    pub fn clear_key(&mut self) {
+        if let Some(name) = &self.key_header {
+            self.headers.remove(name);
+        }
+    }
+
+    /// The default timeout applied to each request sent by this
+    /// client, if any.
+    ///
+    /// # Returns
+    /// - [`Option<Duration>`] - The default timeout, if any.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::new();
+    ///
+    /// assert_eq!(client.get_timeout(), None);
+    /// ```
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the default timeout applied to each request sent by this
+    /// client, replacing any existing timeout.
+    /// [`Client::execute_with_timeout`] overrides it for a single
+    /// request.
+    ///
+    /// # Arguments
+    /// - `timeout` - The maximum time to wait for Piston.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let mut client = piston_rs::Client::new();
+    /// client.set_timeout(Duration::from_secs(10));
+    ///
+    /// assert_eq!(client.get_timeout(), Some(Duration::from_secs(10)));
+    /// ```
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Removes the default timeout used by this client, if any.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let mut client = piston_rs::Client::builder()
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build();
+    /// client.clear_timeout();
+    ///
+    /// assert_eq!(client.get_timeout(), None);
+    /// ```
+    pub fn clear_timeout(&mut self) {
+        self.timeout = None;
+    }
+
+    /// Adds a custom header to send with every request, alongside the
+    /// default headers. Overwrites the header if it is already set.
+    ///
+    /// # Arguments
+    /// - `key` - The name of the header.
+    /// - `value` - The value of the header.
+    ///
+    /// # Panics
+    /// - If the key is not a valid header name, or the value is not a
+    ///   valid header value.
+    ///
+    /// # Example
+    /// ```
+    /// let mut client = piston_rs::Client::new();
+    /// client.add_header("X-Proxy-Token", "secret");
+    ///
+    /// assert_eq!(client.get_headers().get("X-Proxy-Token").unwrap(), "secret");
+    /// assert!(client.get_headers().contains_key("Accept"));
+    /// ```
+    pub fn add_header(&mut self, key: &str, value: &str) {
+        self.headers.insert(
+            HeaderName::from_bytes(key.as_bytes()).unwrap(),
+            HeaderValue::from_str(value).unwrap(),
+        );
+    }
+
+    /// Generates the headers the client should use.
+    ///
+    /// `Accept-Encoding` is not set here. With the `gzip` or `brotli`
+    /// features enabled, reqwest sends it itself, and only decompresses
+    /// responses when it was not set manually.
+    ///
+    /// # Returns
+    ///
+    /// - [`HeaderMap`] - A map of Header key, value pairs.
+    ///
+    /// # Example
+    /// ```ignore # Fails to compile (private function)
+    /// let headers = piston_rs::Client::generate_headers(None);
+    ///
+    /// assert!(!headers.contains_key("Authorization"));
+    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
+    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    ///
+    /// let headers = piston_rs::Client::generate_headers(Some("123abc"));
+    ///
+    /// assert_eq!(headers.get("Authorization").unwrap(), "123abc");
+    /// assert_eq!(headers.get("Accept").unwrap(), "application/json");
+    /// assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    /// ```
+    pub(crate) fn generate_headers(key: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::with_capacity(3);
+        headers.insert("Accept", HeaderValue::from_str("application/json").unwrap());
+        headers.insert("User-Agent", HeaderValue::from_str("piston-rs").unwrap());
+
+        if let Some(k) = key {
+            headers.insert("Authorization", HeaderValue::from_str(k).unwrap());
+        };
+
+        headers
+    }
+
+    /// Fetches the runtimes from Piston. **This is an http request**,
+    /// unless the runtime cache is enabled and still fresh.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
     /// # #[tokio::test]
-    /// # async fn test_execute() {
+    /// # async fn test_fetch_runtimes() {
     /// let client = piston_rs::Client::new();
-    /// let executor = piston_rs::Executor::new()
-    ///     .set_language("rust")
-    ///     .set_version("1.50.0")
-    ///     .add_file(piston_rs::File::default().set_content(
-    ///         "fn main() { println!(\"42\"); }",
-    ///     ));
     ///
-    /// if let Ok(response) = client.execute(&executor).await {
-    ///     assert!(response.compile.is_some());
-    ///     assert!(response.run.is_ok());
-    ///     assert!(response.is_ok());
+    /// if let Ok(runtimes) = client.fetch_runtimes().await {
+    ///     assert!(!runtimes.is_empty());
     /// } else {
     ///     // There was an error contacting Piston.
     /// }
     /// # }
     /// ```
-    pub async fn execute(&self, executor: &Executor) -> Result<ExecResponse, Box<dyn Error>> {
-        let endpoint = format!("{}/execute", self.url);
+    pub async fn fetch_runtimes(&self) -> Result<Vec<Runtime>, PistonError> {
+        Ok(self.fetch_runtimes_cached().await?.0)
+    }
 
-        match self
-            .client
-            .post(endpoint)
-            .headers(self.headers.clone())
-            .json::<Executor>(executor)
-            .send()
-            .await
-        {
-            Ok(data) => {
-                let status = data.status();
-
-                match status {
-                    reqwest::StatusCode::OK => {
-                        let response = data.json::<RawExecResponse>().await?;
-
-                        Ok(ExecResponse {
-                            language: response.language,
-                            version: response.version,
-                            run: response.run,
-                            compile: response.compile,
-                            status: status.as_u16(),
-                        })
+    /// Fetches the runtimes from Piston, like [`Client::fetch_runtimes`],
+    /// and reports whether they came from the runtime cache. **This is
+    /// an http request**, unless the runtime cache is enabled and still
+    /// fresh.
+    ///
+    /// # Returns
+    /// - [`Result<(Vec<Runtime>, bool), PistonError>`] - The available
+    ///   runtimes, and [`true`] if they were served from the cache, or
+    ///   the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_runtimes_cached() {
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new()
+    ///     .with_runtime_cache(Duration::from_secs(3600));
+    ///
+    /// if let Ok((_, cached)) = client.fetch_runtimes_cached().await {
+    ///     assert!(!cached);
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_runtimes_cached(&self) -> Result<(Vec<Runtime>, bool), PistonError> {
+        if let Some(runtimes) = self.cached_runtimes() {
+            return Ok((runtimes, true));
+        }
+
+        Ok((self.refresh_runtimes().await?, false))
+    }
+
+    /// Fetches the runtimes from Piston, bypassing the runtime cache,
+    /// and caches the result if caching is enabled. **This is an http
+    /// request**.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_refresh_runtimes() {
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new()
+    ///     .with_runtime_cache(Duration::from_secs(3600));
+    ///
+    /// if let Ok(runtimes) = client.refresh_runtimes().await {
+    ///     assert!(!runtimes.is_empty());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn refresh_runtimes(&self) -> Result<Vec<Runtime>, PistonError> {
+        let endpoint = format!("{}/runtimes", self.url);
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!("fetch_runtimes", endpoint = %endpoint);
+
+        let request = self.runtimes_request(&endpoint);
+        let response = self.send(request, Self::parse_runtimes);
+
+        #[cfg(feature = "tracing")]
+        let response = crate::trace::instrument(response, span);
+
+        let runtimes = response.await?;
+
+        if let Some(cache) = &self.runtime_cache {
+            if let Ok(mut cache) = cache.lock() {
+                cache.entry = Some((Instant::now(), runtimes.clone()));
+            }
+        }
+
+        Ok(runtimes)
+    }
+
+    /// Fetches the runtimes from Piston one page at a time, yielding
+    /// them as each page arrives. **These are http requests**, and the
+    /// runtime cache is not used.
+    ///
+    /// Pages are followed using the `rel="next"` link in the `Link`
+    /// header, as sent by some self-hosted instances. Piston itself
+    /// sends every runtime in one page. The stream ends after the first
+    /// error, or when a link points to a page that was already fetched.
+    ///
+    /// # Returns
+    /// - [`impl Stream<Item = Result<Runtime, PistonError>>`] - Each
+    ///   runtime, or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_runtimes_stream() {
+    /// use futures_util::StreamExt;
+    ///
+    /// let client = piston_rs::Client::new();
+    /// let mut runtimes = std::pin::pin!(client.fetch_runtimes_stream());
+    ///
+    /// while let Some(Ok(runtime)) = runtimes.next().await {
+    ///     println!("{} {}", runtime.language, runtime.version);
+    /// }
+    /// # }
+    /// ```
+    pub fn fetch_runtimes_stream(
+        &self,
+    ) -> impl Stream<Item = Result<Runtime, PistonError>> + Send + '_ {
+        let first = format!("{}/runtimes", self.url);
+        let page: std::vec::IntoIter<Runtime> = vec![].into_iter();
+
+        stream::unfold(
+            (Some(first), page, HashSet::new()),
+            move |(mut next, mut page, mut visited)| async move {
+                loop {
+                    if let Some(runtime) = page.next() {
+                        return Some((Ok(runtime), (next, page, visited)));
                     }
-                    _ => {
-                        let text = format!("{}: {}", data.status(), data.text().await?);
-
-                        let exec_result = ExecResult {
-                            stdout: String::new(),
-                            stderr: text.clone(),
-                            output: text,
-                            code: 1,
-                            signal: None,
-                        };
-
-                        let exec_response = ExecResponse {
-                            language: executor.language.clone(),
-                            version: executor.version.clone(),
-                            run: exec_result,
-                            compile: None,
-                            status: status.as_u16(),
-                        };
-
-                        Ok(exec_response)
+
+                    // Stops a misbehaving server from looping forever.
+                    let url = next.take().filter(|url| visited.insert(url.clone()))?;
+                    let request = self.runtimes_request(&url);
+
+                    match self.send(request, Self::parse_runtimes_page).await {
+                        Ok((runtimes, link)) => {
+                            let base = reqwest::Url::parse(&url).ok();
+                            next = link.and_then(|l| Some(base?.join(&l).ok()?.into()));
+                            page = runtimes.into_iter();
+                        }
+                        Err(e) => return Some((Err(e), (None, page, visited))),
                     }
                 }
-            }
-            Err(e) => Err(Box::new(e)),
-        }
+            },
+        )
     }
-}
 
-#[cfg(test)]
-mod test_client_private {
-    use super::Client;
+    /// Checks that Piston is reachable and measures how long it takes
+    /// to respond. **This is an http request**.
+    ///
+    /// This fetches the runtimes, bypassing the runtime cache, and
+    /// discards them.
+    ///
+    /// # Returns
+    /// - [`Result<Duration, PistonError>`] - The round trip time, or
+    ///   the error if Piston could not be reached or did not respond
+    ///   with 200.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_ping() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// match client.ping().await {
+    ///     Ok(latency) => println!("Piston responded in {:?}", latency),
+    ///     Err(e) => println!("Piston is unavailable: {}", e),
+    /// }
+    /// # }
+    /// ```
+    pub async fn ping(&self) -> Result<Duration, PistonError> {
+        let endpoint = format!("{}/runtimes", self.url);
+        let request = self.runtimes_request(&endpoint);
+        let started = Instant::now();
 
-    #[test]
-    fn test_gen_headers_no_key() {
-        let headers = Client::generate_headers(None);
+        self.send(request, |status, headers, text| match status {
+            StatusCode::OK => Ok(()),
+            _ => Err(PistonError::from_response(status, headers, text)),
+        })
+        .await?;
 
-        assert!(!headers.contains_key("Authorization"));
-        assert_eq!(headers.get("Accept").unwrap(), "application/json");
-        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+        Ok(started.elapsed())
     }
 
-    #[test]
-    fn test_gen_headers_with_key() {
-        let headers = Client::generate_headers(Some("123abc"));
+    /// Fetches the packages from Piston, both installed and available
+    /// to install. **This is an http request**.
+    ///
+    /// Only self-hosted Piston instances expose packages, so this
+    /// fails with a [`PistonError::Api`] against the public instance.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Package>, PistonError>`] - The packages or the
+    ///   error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_fetch_packages() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    ///
+    /// if let Ok(packages) = client.fetch_packages().await {
+    ///     let available = packages.iter().filter(|p| !p.installed).count();
+    ///     println!("{} packages can be installed", available);
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn fetch_packages(&self) -> Result<Vec<Package>, PistonError> {
+        let endpoint = format!("{}/packages", self.url);
 
-        assert_eq!(headers.get("Authorization").unwrap(), "123abc");
-        assert_eq!(headers.get("Accept").unwrap(), "application/json");
-        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!("fetch_packages", endpoint = %endpoint);
+
+        let mut request = self.client.get(endpoint).headers(self.headers.clone());
+
+        if let Some(t) = self.timeout {
+            request = request.timeout(t);
+        }
+
+        let response = self.send(request, Self::parse_packages);
+
+        #[cfg(feature = "tracing")]
+        let response = crate::trace::instrument(response, span);
+
+        response.await
+    }
+
+    /// Fetches the packages that are installed on Piston. **This is an
+    /// http request**.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Package>, PistonError>`] - The installed packages
+    ///   or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_installed_packages() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    ///
+    /// if let Ok(packages) = client.installed_packages().await {
+    ///     println!("{} packages are installed", packages.len());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn installed_packages(&self) -> Result<Vec<Package>, PistonError> {
+        let mut packages = self.fetch_packages().await?;
+        packages.retain(|p| p.installed);
+        Ok(packages)
+    }
+
+    /// Fetches the packages that are available to install on Piston,
+    /// but not installed yet. **This is an http request**.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Package>, PistonError>`] - The available packages
+    ///   or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_available_packages() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    ///
+    /// if let Ok(packages) = client.available_packages().await {
+    ///     println!("{} packages can be installed", packages.len());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn available_packages(&self) -> Result<Vec<Package>, PistonError> {
+        let mut packages = self.fetch_packages().await?;
+        packages.retain(|p| !p.installed);
+        Ok(packages)
+    }
+
+    /// Installs a package on Piston. **This is an http request**.
+    ///
+    /// Only self-hosted Piston instances allow packages to be
+    /// installed. Others respond with a
+    /// [`PistonError::PackagesUnavailable`].
+    ///
+    /// # Arguments
+    /// - `language` - The language of the package.
+    /// - `version` - The version of the package.
+    ///
+    /// # Returns
+    /// - [`Result<Package, PistonError>`] - The installed package or
+    ///   the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_install_package() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    ///
+    /// match client.install_package("python", "3.10.0").await {
+    ///     Ok(package) => assert!(package.installed),
+    ///     Err(piston_rs::PistonError::PackagesUnavailable { .. }) => {
+    ///         // This instance does not allow installing packages.
+    ///     }
+    ///     Err(_) => {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn install_package(
+        &self,
+        language: &str,
+        version: &str,
+    ) -> Result<Package, PistonError> {
+        let mut package = self
+            .send_package(reqwest::Method::POST, language, version)
+            .await?;
+
+        package.installed = true;
+        Ok(package)
+    }
+
+    /// Uninstalls a package from Piston. **This is an http request**.
+    ///
+    /// Only self-hosted Piston instances allow packages to be
+    /// uninstalled. Others respond with a
+    /// [`PistonError::PackagesUnavailable`].
+    ///
+    /// # Arguments
+    /// - `language` - The language of the package.
+    /// - `version` - The version of the package.
+    ///
+    /// # Returns
+    /// - [`Result<Package, PistonError>`] - The uninstalled package, or
+    ///   a [`PistonError::PackageNotInstalled`] if it was not
+    ///   installed, or any other error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_uninstall_package() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    ///
+    /// match client.uninstall_package("python", "3.10.0").await {
+    ///     Ok(package) => assert!(!package.installed),
+    ///     Err(piston_rs::PistonError::PackageNotInstalled { .. }) => {
+    ///         // There was nothing to uninstall.
+    ///     }
+    ///     Err(_) => {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn uninstall_package(
+        &self,
+        language: &str,
+        version: &str,
+    ) -> Result<Package, PistonError> {
+        let result = self
+            .send_package(reqwest::Method::DELETE, language, version)
+            .await;
+
+        match result {
+            Ok(mut package) => {
+                package.installed = false;
+                Ok(package)
+            }
+            Err(e) => Err(e.with_package(language, version)),
+        }
+    }
+
+    /// Sends a package to the packages endpoint using the given
+    /// method.
+    ///
+    /// # Arguments
+    /// - `method` - The http method to use.
+    /// - `language` - The language of the package.
+    /// - `version` - The version of the package.
+    ///
+    /// # Returns
+    /// - [`Result<Package, PistonError>`] - The package or the error,
+    ///   if any.
+    async fn send_package(
+        &self,
+        method: reqwest::Method,
+        language: &str,
+        version: &str,
+    ) -> Result<Package, PistonError> {
+        let endpoint = format!("{}/packages", self.url);
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "packages",
+            method = %method,
+            endpoint = %endpoint,
+            language = %language,
+            version = %version,
+        );
+
+        let mut request = self
+            .client
+            .request(method, endpoint)
+            .headers(self.headers.clone())
+            .json(&serde_json::json!({ "language": language, "version": version }));
+
+        if let Some(t) = self.timeout {
+            request = request.timeout(t);
+        }
+
+        let response = self.send(request, Self::parse_package);
+
+        #[cfg(feature = "tracing")]
+        let response = crate::trace::instrument(response, span);
+
+        response.await
+    }
+
+    /// Fetches the runtimes from Piston and finds the one matching
+    /// the given language and version. **This is an http request**,
+    /// unless the runtime cache is enabled and still fresh.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    /// - `version` - The exact version, or "*" for the latest
+    ///   available version.
+    ///
+    /// # Returns
+    /// - [`Result<Option<Runtime>, PistonError>`] - The matching
+    ///   runtime, if any, or the error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_get_runtime() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(Some(runtime)) = client.get_runtime("py", "*").await {
+    ///     assert_eq!(runtime.language, "python".to_string());
+    /// } else {
+    ///     // Python is not available, or there was an error.
+    /// }
+    /// # }
+    /// ```
+    pub async fn get_runtime(
+        &self,
+        language: &str,
+        version: &str,
+    ) -> Result<Option<Runtime>, PistonError> {
+        let runtimes = self.fetch_runtimes().await?;
+        let mut matching = runtimes.into_iter().filter(|rt| rt.matches(language));
+
+        match version {
+            "*" => Ok(matching.max_by(compare_runtimes)),
+            _ => Ok(matching.find(|rt| rt.version == version)),
+        }
+    }
+
+    /// Fetches the runtimes from Piston and finds the latest version
+    /// of the given language. **This is an http request**, unless the
+    /// runtime cache is enabled and still fresh.
+    ///
+    /// Versions are compared as semver. Versions that are not valid
+    /// semver sort before those that are, and are compared
+    /// numerically, part by part, amongst themselves.
+    ///
+    /// # Arguments
+    /// - `language` - The language, or one of its aliases.
+    ///
+    /// # Returns
+    /// - [`Result<Option<Runtime>, PistonError>`] - The latest
+    ///   runtime, if any, or the error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_latest_runtime() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(Some(runtime)) = client.latest_runtime("python").await {
+    ///     assert!(runtime.version_semver().is_some());
+    /// } else {
+    ///     // Python is not available, or there was an error.
+    /// }
+    /// # }
+    /// ```
+    pub async fn latest_runtime(&self, language: &str) -> Result<Option<Runtime>, PistonError> {
+        self.get_runtime(language, "*").await
+    }
+
+    /// Fetches the runtimes from Piston and maps each language, and
+    /// each of its aliases, to its latest version. **This is an http
+    /// request**, unless the runtime cache is enabled and still fresh.
+    ///
+    /// Versions are compared the same way as
+    /// [`Client::latest_runtime`].
+    ///
+    /// # Returns
+    /// - [`Result<HashMap<String, String>, PistonError>`] - The latest
+    ///   version of each language and alias, or the error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_default_versions() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(versions) = client.default_versions().await {
+    ///     assert_eq!(versions.get("py"), versions.get("python"));
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn default_versions(&self) -> Result<HashMap<String, String>, PistonError> {
+        let mut latest: HashMap<String, Runtime> = HashMap::new();
+
+        for runtime in self.fetch_runtimes().await? {
+            for name in std::iter::once(&runtime.language).chain(&runtime.aliases) {
+                match latest.get(name) {
+                    Some(rt) if compare_runtimes(rt, &runtime) != Ordering::Less => {}
+                    _ => {
+                        latest.insert(name.clone(), runtime.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(latest
+            .into_iter()
+            .map(|(name, rt)| (name, rt.version))
+            .collect())
+    }
+
+    /// Replaces a "*" version on the executor with the latest version
+    /// Piston has available for its language. Executors with any other
+    /// version are left unchanged. **This is an http request**, unless
+    /// the version is not "*", or the runtime cache is enabled and
+    /// still fresh.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to resolve the version of.
+    ///
+    /// # Returns
+    /// - [`Result<(), PistonError>`] - Nothing, or a
+    ///   [`PistonError::UnknownLanguage`] if Piston has no runtime for
+    ///   the language.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_resolve_version() {
+    /// let client = piston_rs::Client::new();
+    /// let mut executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .set_version("*");
+    ///
+    /// if client.resolve_version(&mut executor).await.is_ok() {
+    ///     assert_ne!(executor.version, "*".to_string());
+    /// } else {
+    ///     // Python is not available, or there was an error.
+    /// }
+    /// # }
+    /// ```
+    pub async fn resolve_version(&self, executor: &mut Executor) -> Result<(), PistonError> {
+        if executor.version != "*" {
+            return Ok(());
+        }
+
+        match self.get_runtime(&executor.language, "*").await? {
+            Some(runtime) => {
+                executor.version = runtime.version;
+                Ok(())
+            }
+            None => Err(PistonError::UnknownLanguage {
+                language: executor.language.clone(),
+            }),
+        }
+    }
+
+    /// Replaces the language on the executor with the name Piston
+    /// reports for it, so aliases like "py" become "python". **This is
+    /// an http request**, unless the runtime cache is enabled and still
+    /// fresh.
+    ///
+    /// If the executor has a specific version, only runtimes with that
+    /// version are considered. Languages that already match a runtime
+    /// exactly are left unchanged.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to resolve the language of.
+    ///
+    /// # Returns
+    /// - [`Result<(), PistonError>`] - Nothing, or a
+    ///   [`PistonError::UnknownLanguage`] if Piston has no runtime for
+    ///   the language.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_resolve_language() {
+    /// let client = piston_rs::Client::new();
+    /// let mut executor = piston_rs::Executor::new().set_language("py");
+    ///
+    /// if client.resolve_language(&mut executor).await.is_ok() {
+    ///     assert_eq!(executor.language, "python".to_string());
+    /// } else {
+    ///     // Python is not available, or there was an error.
+    /// }
+    /// # }
+    /// ```
+    pub async fn resolve_language(&self, executor: &mut Executor) -> Result<(), PistonError> {
+        let runtimes: Vec<Runtime> = self
+            .fetch_runtimes()
+            .await?
+            .into_iter()
+            .filter(|rt| executor.version == "*" || rt.version == executor.version)
+            .collect();
+
+        if runtimes.iter().any(|rt| rt.language == executor.language) {
+            return Ok(());
+        }
+
+        match runtimes
+            .into_iter()
+            .find(|rt| rt.matches(&executor.language))
+        {
+            Some(runtime) => {
+                executor.language = runtime.language;
+                Ok(())
+            }
+            None => Err(PistonError::UnknownLanguage {
+                language: executor.language.clone(),
+            }),
+        }
+    }
+
+    /// Fetches the runtimes from Piston and collects every language
+    /// and alias they support. **This is an http request**, unless the
+    /// runtime cache is enabled and still fresh.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<String>, PistonError>`] - The sorted, deduplicated
+    ///   languages and aliases, or the error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_supported_languages() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(languages) = client.supported_languages().await {
+    ///     assert!(languages.contains(&"py".to_string()));
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn supported_languages(&self) -> Result<Vec<String>, PistonError> {
+        let mut languages: Vec<String> = self
+            .fetch_runtimes()
+            .await?
+            .into_iter()
+            .flat_map(|rt| std::iter::once(rt.language).chain(rt.aliases))
+            .collect();
+
+        languages.sort();
+        languages.dedup();
+        Ok(languages)
+    }
+
+    /// The cached runtimes, if caching is enabled and they have not
+    /// expired.
+    ///
+    /// # Returns
+    /// - [`Option<Vec<Runtime>>`] - The cached runtimes, if any.
+    fn cached_runtimes(&self) -> Option<Vec<Runtime>> {
+        let cache = self.runtime_cache.as_ref()?.lock().ok()?;
+        let (fetched_at, runtimes) = cache.entry.as_ref()?;
+
+        match fetched_at.elapsed() < cache.ttl {
+            true => Some(runtimes.clone()),
+            false => None,
+        }
+    }
+
+    /// Executes code using a given executor. **This is an http
+    /// request**.
+    ///
+    /// The executor is validated with [`Executor::validate`] first,
+    /// and nothing is sent to Piston if it is invalid.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute() {
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .set_version("1.50.0")
+    ///     .add_file(piston_rs::File::default().set_content(
+    ///         "fn main() { println!(\"42\"); }",
+    ///     ));
+    ///
+    /// match client.execute(&executor).await {
+    ///     Ok(response) => {
+    ///         assert!(response.compile.is_some());
+    ///         assert!(response.run.is_ok());
+    ///         assert!(response.is_ok());
+    ///     }
+    ///     Err(piston_rs::PistonError::RateLimited { .. }) => {
+    ///         // Piston is rate limiting us, try again later.
+    ///     }
+    ///     Err(_) => {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute(&self, executor: &Executor) -> Result<ExecResponse, PistonError> {
+        self.send_execute(executor, None, self.headers.clone())
+            .await
+    }
+
+    /// Executes many executors, with at most `concurrency` requests
+    /// in flight at once. **These are http requests**.
+    ///
+    /// The results are returned in the same order as the executors.
+    /// Each executor is executed with [`Client::execute_with_policy`],
+    /// so rate limited requests are retried as the policy allows. When
+    /// Piston last reported no requests remaining, each request also
+    /// waits for the rate limit window to reset before it is sent, so
+    /// [`Client::can_execute`] is [`true`] for it.
+    ///
+    /// # Arguments
+    /// - `executors` - The executors to use.
+    /// - `concurrency` - The maximum number of requests in flight at
+    ///   once. Treated as 1 if 0.
+    /// - `policy` - Which failures to retry, and how.
+    ///
+    /// # Returns
+    /// - [`Vec<Result<ExecResponse, PistonError>>`] - The response
+    ///   from Piston or the error, for each executor.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_many() {
+    /// let client = piston_rs::Client::new();
+    /// let policy = piston_rs::RetryPolicy::new();
+    /// let executors: Vec<_> = (0..10)
+    ///     .map(|i| {
+    ///         piston_rs::Executor::new()
+    ///             .set_language("python")
+    ///             .add_file(piston_rs::File::default().set_content(&format!("print({})", i)))
+    ///     })
+    ///     .collect();
+    ///
+    /// for result in client.execute_many(&executors, 2, &policy).await {
+    ///     if let Ok(response) = result {
+    ///         assert!(response.is_ok());
+    ///     } else {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_many(
+        &self,
+        executors: &[Executor],
+        concurrency: usize,
+        policy: &RetryPolicy,
+    ) -> Vec<Result<ExecResponse, PistonError>> {
+        stream::iter(executors)
+            .map(|executor| async move {
+                let wait = self.rate_limit_wait();
+
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+
+                self.execute_with_policy(executor, policy).await
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Executes a single snippet of code. **This is an http
+    /// request**.
+    ///
+    /// This is a shortcut for building an [`Executor`] with
+    /// [`Executor::from_content`] and passing it to
+    /// [`Client::execute`].
+    ///
+    /// # Arguments
+    /// - `language` - The language to use.
+    /// - `version` - The version of the language to use.
+    /// - `code` - The code to execute.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_snippet() {
+    /// let client = piston_rs::Client::new();
+    ///
+    /// if let Ok(response) = client.execute_snippet("python", "*", "print(42)").await {
+    ///     assert_eq!(response.run.stdout, "42\n".to_string());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_snippet(
+        &self,
+        language: &str,
+        version: &str,
+        code: &str,
+    ) -> Result<ExecResponse, PistonError> {
+        let executor = Executor::from_content(language, version, code);
+        self.execute(&executor).await
+    }
+
+    /// Executes code using a given executor, failing if Piston does
+    /// not respond within the given timeout. **This is an http
+    /// request**.
+    ///
+    /// The timeout covers the whole request, from connecting until
+    /// the response body has been read. When it elapses, a
+    /// [`PistonError::Timeout`] with the endpoint and elapsed time is
+    /// returned, and [`PistonError::is_timeout`] returns [`true`] for
+    /// it. Piston only sends the output once the program has finished,
+    /// so any output produced before the timeout is lost. Use
+    /// `execute_streaming`, with the `ws` feature, to receive output
+    /// as it is produced instead.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `timeout` - The maximum time to wait for Piston.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_timeout() {
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust")
+    ///     .set_version("1.50.0")
+    ///     .add_file(piston_rs::File::default().set_content(
+    ///         "fn main() { println!(\"42\"); }",
+    ///     ));
+    ///
+    /// match client.execute_with_timeout(&executor, Duration::from_secs(5)).await {
+    ///     Ok(response) => assert!(response.is_ok()),
+    ///     Err(e) if e.is_timeout() => {
+    ///         // Piston took too long to respond.
+    ///     }
+    ///     Err(_) => {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_timeout(
+        &self,
+        executor: &Executor,
+        timeout: Duration,
+    ) -> Result<ExecResponse, PistonError> {
+        self.send_execute(executor, Some(timeout), self.headers.clone())
+            .await
+    }
+
+    /// Executes code using a given executor, sending extra headers
+    /// with this request only. **This is an http request**.
+    ///
+    /// The extra headers are added on top of the client's headers,
+    /// replacing any with the same name. The headers stored in this
+    /// client are left as is.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `extra` - The headers to add to this request.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_headers() {
+    /// use reqwest::header::{HeaderMap, HeaderValue};
+    ///
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// let mut extra = HeaderMap::new();
+    /// extra.insert("X-Correlation-Id", HeaderValue::from_static("abc123"));
+    ///
+    /// if let Ok(response) = client.execute_with_headers(&executor, extra).await {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_headers(
+        &self,
+        executor: &Executor,
+        extra: HeaderMap,
+    ) -> Result<ExecResponse, PistonError> {
+        let mut headers = self.headers.clone();
+        headers.extend(extra);
+
+        self.send_execute(executor, None, headers).await
+    }
+
+    /// Executes code using a given executor, without sending the api
+    /// key. **This is an http request**.
+    ///
+    /// Only this request omits the header the key is sent in, which is
+    /// `Authorization` unless another was set with
+    /// [`ClientBuilder::key_header`]. The key stored in this client is
+    /// left as is, and basic auth credentials are still sent.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_without_auth() {
+    /// let client = piston_rs::Client::with_key("123abc");
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// if let Ok(response) = client.execute_without_auth(&executor).await {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_without_auth(
+        &self,
+        executor: &Executor,
+    ) -> Result<ExecResponse, PistonError> {
+        let mut headers = self.headers.clone();
+
+        if let Some(name) = &self.key_header {
+            headers.remove(name);
+        }
+
+        self.send_execute(executor, None, headers).await
+    }
+
+    /// Describes the request [`Client::execute`] would send for the
+    /// given executor, without sending it. The description has the
+    /// method and endpoint, the headers, with the api key redacted,
+    /// and the JSON body.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to describe.
+    ///
+    /// # Returns
+    /// - [`String`] - The description of the request.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::Client::with_key("123abc");
+    /// let executor = piston_rs::Executor::new().set_language("python");
+    /// let request = client.dry_run(&executor);
+    ///
+    /// assert!(request.starts_with("POST https://emkc.org/api/v2/piston/execute\n"));
+    /// assert!(request.contains("authorization: <redacted>\n"));
+    /// assert!(!request.contains("123abc"));
+    /// ```
+    pub fn dry_run(&self, executor: &Executor) -> String {
+        let mut request = format!("POST {}/execute\n", self.url);
+
+        for (name, value) in &self.headers {
+            let value = match name.as_str() {
+                "authorization" => "<redacted>",
+                _ if value.is_sensitive() => "<redacted>",
+                _ => value.to_str().unwrap_or("<binary>"),
+            };
+
+            request.push_str(&format!("{}: {}\n", name, value));
+        }
+
+        let body = executor.to_json().unwrap_or_else(|e| e.to_string());
+        request.push('\n');
+        request.push_str(&body);
+        request
+    }
+
+    /// Executes code using a given executor, returning Piston's
+    /// response as is. **This is an http request**.
+    ///
+    /// This is an escape hatch for when you need something
+    /// [`ExecResponse`] does not have, like the response headers. The
+    /// status code is not checked, so responses other than 200 are
+    /// returned as `Ok` too. Use [`Client::execute`] for the typed
+    /// response.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<reqwest::Response, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_raw() {
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// if let Ok(response) = client.execute_raw(&executor).await {
+    ///     println!("{:?}", response.headers().get("X-Request-Id"));
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_raw(&self, executor: &Executor) -> Result<reqwest::Response, PistonError> {
+        executor.validate()?;
+
+        Ok(self
+            .execute_request(executor, None, self.headers.clone())
+            .send()
+            .await?)
+    }
+
+    /// Executes code using a given executor, streaming the request
+    /// body instead of serializing it all at once. **This is an http
+    /// request**.
+    ///
+    /// [`Client::execute`] builds the whole JSON body in memory before
+    /// sending it, which is a second copy of every file. This takes
+    /// ownership of the executor and writes the content of its files
+    /// to the connection in chunks, so submissions with very large
+    /// files use little more memory than the executor itself.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_large() {
+    /// let data = "1,2,3\n".repeat(1_000_000);
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::from("print(len(open('data.csv').read()))"))
+    ///     .add_file(piston_rs::File::default().set_name("data.csv").set_content(&data));
+    ///
+    /// if let Ok(response) = client.execute_large(executor).await {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_large(&self, mut executor: Executor) -> Result<ExecResponse, PistonError> {
+        executor.validate()?;
+
+        let files = std::mem::take(&mut executor.files);
+        let body = large_body(&executor, files)?;
+        let endpoint = format!("{}/execute", self.url);
+        let mut request = self
+            .client
+            .post(endpoint)
+            .headers(self.headers.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+
+        if let Some(t) = self.timeout {
+            request = request.timeout(t);
+        }
+
+        self.send_execute_request(&executor, request).await
+    }
+
+    /// Executes code using a given executor, retrying with a longer
+    /// run timeout when the run step is killed for taking too long.
+    /// **These are http requests**.
+    ///
+    /// The first attempt uses a run timeout of `initial` milliseconds.
+    /// Each time the process is killed by the timeout, the timeout is
+    /// doubled, up to `max`, and the code is executed again. A slow
+    /// program will eventually finish, while an infinite loop is still
+    /// killed at `max`.
+    ///
+    /// ##### Note
+    /// Piston also sends `SIGKILL` when the memory limit is exceeded,
+    /// and those runs aren't retried. A run counts as timed out when
+    /// its [`ExecResult::stage_status`] is "TO", or when it was killed and
+    /// its [`ExecResult::wall_time`] reached the run timeout. Older
+    /// versions of Piston send neither, so then a killed run counts as
+    /// timed out if the whole request took at least as long as the run
+    /// timeout, which includes the time spent compiling.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use. Its run timeout is replaced.
+    /// - `initial` - The run timeout of the first attempt, in
+    ///   milliseconds. It is lowered to `max` if it is larger.
+    /// - `max` - The longest run timeout to try, in milliseconds.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The last response
+    ///   from Piston or the error, if any. If `initial` or `max` is not
+    ///   positive, a [`PistonError::InvalidExecutor`] is returned and
+    ///   nothing is sent.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_adaptive_timeout() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("import time; time.sleep(2)"));
+    ///
+    /// if let Ok(response) = client.execute_with_adaptive_timeout(&executor, 1000, 8000).await {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_adaptive_timeout(
+        &self,
+        executor: &Executor,
+        initial: isize,
+        max: isize,
+    ) -> Result<ExecResponse, PistonError> {
+        if initial <= 0 || max <= 0 {
+            return Err(PistonError::InvalidExecutor(format!(
+                "Run timeouts must be positive, got {} and {}",
+                initial, max
+            )));
+        }
+
+        let mut executor = executor.clone().set_run_timeout(initial.min(max));
+
+        loop {
+            let response = self.execute(&executor).await?;
+            let timeout = executor.run_timeout;
+            let next = timeout.saturating_mul(2).min(max);
+
+            if next <= timeout || !killed_by_timeout(&response, timeout) {
+                return Ok(response);
+            }
+
+            executor.run_timeout = next;
+        }
+    }
+
+    /// Executes code using a given executor, retrying when Piston
+    /// rate limits the request. **This is an http request**.
+    ///
+    /// When Piston responds with HTTP 429, this waits for as long as
+    /// the `Retry-After` header asks, or backs off exponentially if it
+    /// is missing, before trying again. Unless disabled with
+    /// [`ClientBuilder::retry_jitter`], the backoff is randomized
+    /// between zero and the full delay, and a random delay of up to the
+    /// backoff is added on top of `Retry-After`, so many clients
+    /// don't retry in lockstep. Once `max_retries` retries
+    /// have been made, the final [`PistonError::RateLimited`] is
+    /// returned. Any other error is returned immediately.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `max_retries` - The maximum number of times to retry.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_retry() {
+    /// let client = piston_rs::Client::new();
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// match client.execute_with_retry(&executor, 3).await {
+    ///     Ok(response) => assert!(response.is_ok()),
+    ///     Err(piston_rs::PistonError::RateLimited { .. }) => {
+    ///         // Still rate limited after 3 retries.
+    ///     }
+    ///     Err(_) => {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_retry(
+        &self,
+        executor: &Executor,
+        max_retries: u32,
+    ) -> Result<ExecResponse, PistonError> {
+        let policy = RetryPolicy::new()
+            .max_retries(max_retries)
+            .statuses(&[])
+            .retry_connection_errors(false);
+
+        self.execute_with_policy(executor, &policy).await
+    }
+
+    /// Executes code using a given executor, retrying failures the
+    /// policy allows. **This is an http request**.
+    ///
+    /// Rate limited requests are retried like
+    /// [`Client::execute_with_retry`] does. Other retryable failures
+    /// back off exponentially, with jitter unless it was disabled with
+    /// [`ClientBuilder::retry_jitter`]. Once the policy's maximum
+    /// number of retries have been made, the final error is returned.
+    /// Any error the policy does not retry is returned immediately.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `policy` - Which failures to retry, and how.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_with_policy() {
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    /// let policy = piston_rs::RetryPolicy::new().max_retries(5);
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// if let Ok(response) = client.execute_with_policy(&executor, &policy).await {
+    ///     assert!(response.is_ok());
+    /// } else {
+    ///     // Piston was still failing after 5 retries.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_with_policy(
+        &self,
+        executor: &Executor,
+        policy: &RetryPolicy,
+    ) -> Result<ExecResponse, PistonError> {
+        let mut attempt = 0;
+
+        loop {
+            match self.execute(executor).await {
+                Err(e) if attempt < policy.max_retries && policy.retries(&e) => {
+                    let backoff = policy.delay(attempt);
+                    let delay = match (e.retry_after(), self.retry_jitter) {
+                        (Some(d), true) => d + jitter(backoff),
+                        (Some(d), false) => d,
+                        (None, true) => jitter(backoff),
+                        (None, false) => backoff,
+                    };
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends the executor to Piston, applying the timeout if one was
+    /// given, or the client's default timeout otherwise.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `timeout` - The optional timeout for the request.
+    /// - `headers` - The headers to send.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    async fn send_execute(
+        &self,
+        executor: &Executor,
+        timeout: Option<Duration>,
+        headers: HeaderMap,
+    ) -> Result<ExecResponse, PistonError> {
+        executor.validate()?;
+
+        let request = self.execute_request(executor, timeout, headers);
+        self.send_execute_request(executor, request).await
+    }
+
+    /// Sends a request built for the executor to Piston, and parses
+    /// the response.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor the request was built for. Only its
+    ///   language and version are used.
+    /// - `request` - The request to send.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    async fn send_execute_request(
+        &self,
+        executor: &Executor,
+        request: reqwest::RequestBuilder,
+    ) -> Result<ExecResponse, PistonError> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "execute",
+            endpoint = %format!("{}/execute", self.url),
+            language = %executor.language,
+            version = %executor.version,
+        );
+
+        let response = self.send(request, Self::parse_execute);
+
+        #[cfg(feature = "tracing")]
+        let response = crate::trace::instrument(response, span);
+
+        let started = Instant::now();
+        let mut response = response.await.map_err(|e| {
+            e.with_timeout(&format!("{}/execute", self.url), started.elapsed())
+                .with_language(&executor.language)
+        })?;
+
+        response.elapsed = Some(started.elapsed());
+        Ok(response)
+    }
+
+    /// Reads the body of a response, failing once it exceeds the
+    /// maximum size, if one was set.
+    ///
+    /// # Arguments
+    /// - `data` - The response to read.
+    ///
+    /// # Returns
+    /// - [`Result<String, PistonError>`] - The body or the error, if
+    ///   any.
+    async fn read_body(&self, mut data: reqwest::Response) -> Result<String, PistonError> {
+        let limit = match self.max_output_bytes {
+            Some(limit) => limit,
+            None => return Ok(data.text().await?),
+        };
+
+        let mut body = Vec::new();
+
+        while let Some(chunk) = data.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(PistonError::OutputTooLarge { limit });
+            }
+
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Builds the request used to fetch runtimes, applying the runtime
+    /// fetch timeout, or the default timeout if there isn't one.
+    ///
+    /// # Arguments
+    /// - `url` - The url to fetch the runtimes from.
+    ///
+    /// # Returns
+    /// - [`reqwest::RequestBuilder`] - The request.
+    fn runtimes_request(&self, url: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.get(url).headers(self.headers.clone());
+
+        if let Some(t) = self.runtime_fetch_timeout.or(self.timeout) {
+            request = request.timeout(t);
+        }
+
+        request
+    }
+
+    /// Builds the request that sends the executor to Piston, applying
+    /// the timeout if one was given, or the client's default timeout
+    /// otherwise.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `timeout` - The optional timeout for the request.
+    /// - `headers` - The headers to send.
+    ///
+    /// # Returns
+    /// - [`reqwest::RequestBuilder`] - The request, ready to send.
+    fn execute_request(
+        &self,
+        executor: &Executor,
+        timeout: Option<Duration>,
+        headers: HeaderMap,
+    ) -> reqwest::RequestBuilder {
+        let endpoint = format!("{}/execute", self.url);
+        let mut request = self
+            .client
+            .post(endpoint)
+            .headers(headers)
+            .json::<Executor>(executor);
+
+        if let Some(t) = timeout.or(self.timeout) {
+            request = request.timeout(t);
+        }
+
+        request
+    }
+
+    /// Sends a request to Piston and parses the response, recording
+    /// the rate limit Piston reported.
+    ///
+    /// # Arguments
+    /// - `request` - The request to send.
+    /// - `parse` - The function used to parse the response.
+    ///
+    /// # Returns
+    /// - [`Result<T, PistonError>`] - The parsed response or the
+    ///   error, if any.
+    async fn send<T>(
+        &self,
+        request: reqwest::RequestBuilder,
+        parse: fn(StatusCode, &HeaderMap, String) -> Result<T, PistonError>,
+    ) -> Result<T, PistonError> {
+        let data = request.send().await?;
+        let status = data.status();
+        let headers = data.headers().clone();
+
+        if let Some(rate_limit) = RateLimit::from_headers(&headers) {
+            if let Ok(mut latest) = self.rate_limit.lock() {
+                *latest = Some((rate_limit, Instant::now()));
+            }
+        }
+
+        let text = self.read_body(data).await?;
+
+        parse(status, &headers, text)
+    }
+
+    /// Parses the response Piston sent when fetching runtimes.
+    ///
+    /// # Arguments
+    /// - `status` - The status code of the response.
+    /// - `headers` - The headers of the response.
+    /// - `text` - The body of the response.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Runtime>, PistonError>`] - The available
+    ///   runtimes or the error, if any.
+    pub(crate) fn parse_runtimes(
+        status: StatusCode,
+        headers: &HeaderMap,
+        text: String,
+    ) -> Result<Vec<Runtime>, PistonError> {
+        match status {
+            StatusCode::OK => Ok(serde_json::from_str::<Vec<Runtime>>(&text)?),
+            _ => Err(PistonError::from_response(status, headers, text)),
+        }
+    }
+
+    /// Parses a page of runtimes, along with the link to the next
+    /// page, if any.
+    ///
+    /// # Arguments
+    /// - `status` - The status code of the response.
+    /// - `headers` - The headers of the response.
+    /// - `text` - The body of the response.
+    ///
+    /// # Returns
+    /// - [`Result<(Vec<Runtime>, Option<String>), PistonError>`] - The
+    ///   runtimes and the next link, or the error, if any.
+    fn parse_runtimes_page(
+        status: StatusCode,
+        headers: &HeaderMap,
+        text: String,
+    ) -> Result<(Vec<Runtime>, Option<String>), PistonError> {
+        let runtimes = Self::parse_runtimes(status, headers, text)?;
+        let next = headers
+            .get_all(LINK)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .find_map(next_link);
+
+        Ok((runtimes, next))
+    }
+
+    /// Parses the response Piston sent when fetching packages.
+    ///
+    /// # Arguments
+    /// - `status` - The status code of the response.
+    /// - `headers` - The headers of the response.
+    /// - `text` - The body of the response.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<Package>, PistonError>`] - The packages or the
+    ///   error, if any.
+    fn parse_packages(
+        status: StatusCode,
+        headers: &HeaderMap,
+        text: String,
+    ) -> Result<Vec<Package>, PistonError> {
+        match status {
+            StatusCode::OK => Ok(serde_json::from_str::<Vec<Package>>(&text)?),
+            _ => Err(PistonError::from_response(status, headers, text)),
+        }
+    }
+
+    /// Parses the response Piston sent when installing or
+    /// uninstalling a package.
+    ///
+    /// # Arguments
+    /// - `status` - The status code of the response.
+    /// - `headers` - The headers of the response.
+    /// - `text` - The body of the response.
+    ///
+    /// # Returns
+    /// - [`Result<Package, PistonError>`] - The package or the error,
+    ///   if any.
+    fn parse_package(
+        status: StatusCode,
+        headers: &HeaderMap,
+        text: String,
+    ) -> Result<Package, PistonError> {
+        match status {
+            StatusCode::OK => Ok(serde_json::from_str::<Package>(&text)?),
+            StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => {
+                Err(PistonError::PackagesUnavailable {
+                    status: status.as_u16(),
+                })
+            }
+            _ => Err(PistonError::from_response(status, headers, text)),
+        }
+    }
+
+    /// Parses the response Piston sent when executing code.
+    ///
+    /// # Arguments
+    /// - `status` - The status code of the response.
+    /// - `headers` - The headers of the response.
+    /// - `text` - The body of the response.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The response
+    ///   from Piston or the error, if any.
+    pub(crate) fn parse_execute(
+        status: StatusCode,
+        headers: &HeaderMap,
+        text: String,
+    ) -> Result<ExecResponse, PistonError> {
+        match status {
+            StatusCode::OK => {
+                let response = serde_json::from_str::<RawExecResponse>(&text)?;
+
+                Ok(ExecResponse {
+                    language: response.language,
+                    version: response.version,
+                    run: response.run,
+                    compile: response.compile,
+                    status: status.as_u16(),
+                    elapsed: None,
+                })
+            }
+            _ => Err(PistonError::from_response(status, headers, text)),
+        }
+    }
+}
+
+/// A builder used to configure a new [`Client`].
+///
+/// Any option that is not set keeps the same default as
+/// [`Client::new`].
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    /// The base url for Piston.
+    url: String,
+    /// The api key to use, if any.
+    key: Option<String>,
+    /// The header to send the api key in, if not `Authorization`.
+    key_header: Option<String>,
+    /// The username and password for basic auth, if any.
+    basic_auth: Option<(String, String)>,
+    /// The default timeout for each request, if any.
+    timeout: Option<Duration>,
+    /// The user agent to send, if not the default.
+    user_agent: Option<String>,
+    /// The custom headers to send with every request.
+    headers: Vec<(String, String)>,
+    /// The proxies to route requests through.
+    proxies: Vec<reqwest::Proxy>,
+    /// The timeout for fetching runtimes, if any.
+    runtime_fetch_timeout: Option<Duration>,
+    /// The maximum size of a response body, if any.
+    max_output_bytes: Option<usize>,
+    /// The maximum number of idle connections kept per host, if not
+    /// the reqwest default.
+    pool_max_idle_per_host: Option<usize>,
+    /// The TCP keepalive interval, if any.
+    tcp_keepalive: Option<Duration>,
+    /// Whether or not to only use HTTP/2.
+    http2_prior_knowledge: bool,
+    /// Whether or not to add random jitter to retry delays.
+    retry_jitter: bool,
+    /// The runtime cache to start with, if any.
+    cache: Option<CacheSnapshot>,
+    /// Extra root certificates to trust.
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    root_certificates: Vec<reqwest::Certificate>,
+    /// Whether or not to accept invalid TLS certificates.
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    danger_accept_invalid_certs: bool,
+}
+
+impl Default for ClientBuilder {
+    /// Creates a new builder. Alias for [`ClientBuilder::new`].
+    ///
+    /// # Returns
+    /// - [`ClientBuilder`] - The new builder.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a new builder, with the same defaults as
+    /// [`Client::new`].
+    ///
+    /// # Returns
+    /// - [`ClientBuilder`] - The new builder.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new().build();
+    ///
+    /// assert!(!client.get_headers().contains_key("Authorization"));
+    /// assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston".to_string());
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            key: None,
+            key_header: None,
+            basic_auth: None,
+            timeout: None,
+            user_agent: None,
+            headers: vec![],
+            proxies: vec![],
+            runtime_fetch_timeout: None,
+            max_output_bytes: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            http2_prior_knowledge: false,
+            retry_jitter: true,
+            cache: None,
+            #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+            root_certificates: vec![],
+            #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+            danger_accept_invalid_certs: false,
+        }
+    }
+
+    /// Sets the url to use as the underlying piston backend.
+    ///
+    /// # Arguments
+    /// - `url` - The url to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .url("http://localhost:3000")
+    ///     .build();
+    ///
+    /// assert_eq!(client.get_url(), "http://localhost:3000");
+    /// ```
+    #[must_use]
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Sets the api key to use.
+    ///
+    /// # Arguments
+    /// - `key` - The api key to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .key("123abc")
+    ///     .build();
+    ///
+    /// assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    /// ```
+    #[must_use]
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    /// Sets the header the api key is sent in, for instances that
+    /// expect it somewhere other than `Authorization`, i.e.
+    /// `X-Api-Key`. This lets the key be used alongside
+    /// [`ClientBuilder::basic_auth`].
+    ///
+    /// # Arguments
+    /// - `name` - The name of the header.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .key("123abc")
+    ///     .key_header("X-Api-Key")
+    ///     .build();
+    ///
+    /// assert_eq!(client.get_headers().get("X-Api-Key").unwrap(), "123abc");
+    /// assert!(!client.get_headers().contains_key("Authorization"));
+    /// ```
+    #[must_use]
+    pub fn key_header(mut self, name: &str) -> Self {
+        self.key_header = Some(name.to_string());
+        self
+    }
+
+    /// Sets the username and password to send with HTTP basic auth,
+    /// i.e. for a Piston instance behind a reverse proxy that requires
+    /// it.
+    ///
+    /// Basic auth uses the `Authorization` header, so it replaces the
+    /// api key unless the key is moved to another header with
+    /// [`ClientBuilder::key_header`].
+    ///
+    /// # Arguments
+    /// - `username` - The username.
+    /// - `password` - The password.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .basic_auth("piston", "hunter2")
+    ///     .key("123abc")
+    ///     .key_header("X-Api-Key")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     client.get_headers().get("Authorization").unwrap(),
+    ///     "Basic cGlzdG9uOmh1bnRlcjI="
+    /// );
+    /// assert_eq!(client.get_headers().get("X-Api-Key").unwrap(), "123abc");
+    /// ```
+    #[must_use]
+    pub fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.basic_auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Sets the default timeout for each request. Timeouts passed to
+    /// methods like [`Client::execute_with_timeout`] take precedence.
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for fetching runtimes, overriding the default
+    /// timeout for those requests only. Useful for keeping a tight
+    /// bound on runtime fetches while executions take longer.
+    ///
+    /// # Arguments
+    /// - `timeout` - The timeout to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .timeout(Duration::from_secs(30))
+    ///     .runtime_fetch_timeout(Duration::from_secs(2))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn runtime_fetch_timeout(mut self, timeout: Duration) -> Self {
+        self.runtime_fetch_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header to send. Defaults to "piston-rs".
+    ///
+    /// # Arguments
+    /// - `user_agent` - The user agent to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .user_agent("my-bot/1.2.3")
+    ///     .build();
+    ///
+    /// assert_eq!(client.get_headers().get("User-Agent").unwrap(), "my-bot/1.2.3");
+    /// ```
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Adds a custom header to send with every request, alongside the
+    /// default headers.
+    ///
+    /// # Arguments
+    /// - `key` - The name of the header.
+    /// - `value` - The value of the header.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .header("X-Proxy-Token", "secret")
+    ///     .build();
+    ///
+    /// assert_eq!(client.get_headers().get("X-Proxy-Token").unwrap(), "secret");
+    /// ```
+    #[must_use]
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds a proxy to route requests through.
+    ///
+    /// Without any proxies, the standard `HTTP_PROXY`, `HTTPS_PROXY`,
+    /// and `NO_PROXY` environment variables are honored. Adding a
+    /// proxy replaces that behavior.
+    ///
+    /// # Arguments
+    /// - `proxy` - The proxy to use.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let proxy = reqwest::Proxy::https("http://proxy.example.com:8080").unwrap();
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .proxy(proxy)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Sets the maximum size of a response body, so programs that
+    /// produce huge amounts of output can't exhaust memory. Reading a
+    /// larger response stops as soon as the limit is exceeded, and
+    /// fails with a [`PistonError::OutputTooLarge`].
+    ///
+    /// # Arguments
+    /// - `max_output_bytes` - The maximum size of a response body, in
+    ///   bytes.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .max_output_bytes(1024 * 1024)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = Some(max_output_bytes);
+        self
+    }
+
+    /// Sets the maximum number of idle connections to Piston kept
+    /// open for reuse. Defaults to no limit, like reqwest.
+    ///
+    /// # Arguments
+    /// - `max` - The maximum number of idle connections.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .pool_max_idle_per_host(32)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Enables TCP keepalive on connections to Piston, sending
+    /// keepalive probes at the given interval. Disabled by default,
+    /// like reqwest.
+    ///
+    /// # Arguments
+    /// - `interval` - The keepalive interval.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .tcp_keepalive(Duration::from_secs(60))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Only use HTTP/2 when talking to Piston, without negotiating
+    /// it first. Only enable this if Piston, or the proxy in front of
+    /// it, is known to support HTTP/2.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .url("http://localhost:2000/api/v2")
+    ///     .http2_prior_knowledge()
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Sets whether or not [`Client::execute_with_retry`] and
+    /// [`Client::execute_with_policy`] add random jitter to their retry
+    /// delays, so many clients that fail at once don't retry in
+    /// lockstep. Defaults to [`true`].
+    ///
+    /// # Arguments
+    /// - `enabled` - Whether or not to add jitter.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .retry_jitter(false)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn retry_jitter(mut self, enabled: bool) -> Self {
+        self.retry_jitter = enabled;
+        self
+    }
+
+    /// Trusts an extra root certificate, i.e. for a self-hosted Piston
+    /// instance using a self-signed certificate. Requires a TLS
+    /// feature.
+    ///
+    /// # Arguments
+    /// - `certificate` - The certificate to trust.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let pem = std::fs::read("piston.pem").unwrap();
+    /// let certificate = reqwest::Certificate::from_pem(&pem).unwrap();
+    ///
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .url("https://piston.internal/api/v2")
+    ///     .add_root_certificate(certificate)
+    ///     .build();
+    /// ```
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    #[must_use]
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Sets whether or not to accept invalid TLS certificates,
+    /// including expired ones and ones for other hosts. Requires a TLS
+    /// feature.
+    ///
+    /// ##### Warning
+    ///
+    /// **This is dangerous.** Any certificate is trusted, so anyone
+    /// able to intercept the connection can read and alter requests,
+    /// including the api key. Prefer
+    /// [`ClientBuilder::add_root_certificate`], and only use this
+    /// against trusted development instances.
+    ///
+    /// # Arguments
+    /// - `accept` - Whether or not to accept invalid certificates.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .url("https://localhost:2000/api/v2")
+    ///     .danger_accept_invalid_certs(true)
+    ///     .build();
+    /// ```
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    #[must_use]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Restores a runtime cache taken with [`Client::export_cache`].
+    ///
+    /// Caching is enabled with the snapshot's ttl, and its runtimes are
+    /// used until they expire, counting from when they were originally
+    /// fetched. A snapshot taken without caching enabled leaves it
+    /// disabled.
+    ///
+    /// # Arguments
+    /// - `snapshot` - The snapshot to restore.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let snapshot = piston_rs::Client::new()
+    ///     .with_runtime_cache(Duration::from_secs(3600))
+    ///     .export_cache();
+    /// let json = serde_json::to_string(&snapshot).unwrap();
+    ///
+    /// let client = piston_rs::ClientBuilder::new()
+    ///     .with_cache(serde_json::from_str(&json).unwrap())
+    ///     .build();
+    ///
+    /// assert_eq!(client.export_cache(), snapshot);
+    /// ```
+    #[must_use]
+    pub fn with_cache(mut self, snapshot: CacheSnapshot) -> Self {
+        self.cache = Some(snapshot);
+        self
+    }
+
+    /// Builds the configured [`Client`].
+    ///
+    /// # Returns
+    /// - [`Client`] - The new Client.
+    ///
+    /// # Panics
+    /// - If the key, user agent, or any custom header is not a valid
+    ///   header.
+    /// - If the underlying reqwest client cannot be built, in the same
+    ///   cases as [`reqwest::Client::new`].
+    pub fn build(self) -> Client {
+        let mut http = reqwest::Client::builder();
+
+        for proxy in self.proxies {
+            http = http.proxy(proxy);
+        }
+
+        if let Some(max) = self.pool_max_idle_per_host {
+            http = http.pool_max_idle_per_host(max);
+        }
+
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        {
+            for certificate in self.root_certificates {
+                http = http.add_root_certificate(certificate);
+            }
+
+            http = http.danger_accept_invalid_certs(self.danger_accept_invalid_certs);
+        }
+
+        if let Some(interval) = self.tcp_keepalive {
+            http = http.tcp_keepalive(interval);
+        }
+
+        if self.http2_prior_knowledge {
+            http = http.http2_prior_knowledge();
+        }
+
+        let http = http.build().expect("Unable to build the reqwest client");
+        let key = self.key.as_deref().filter(|_| self.key_header.is_none());
+        let mut client = Client::with_reqwest_client(http, &self.url, key);
+
+        if let Some(name) = &self.key_header {
+            client.key_header = Some(HeaderName::from_bytes(name.as_bytes()).unwrap());
+
+            if let Some(key) = &self.key {
+                client.set_key(key);
+            }
+        } else if self.basic_auth.is_some() {
+            client.key_header = None;
+        }
+
+        if let Some((username, password)) = &self.basic_auth {
+            use base64::Engine;
+
+            let credentials = format!("{}:{}", username, password);
+            let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+            client.add_header("Authorization", &format!("Basic {}", encoded));
+        }
+
+        if let Some(ua) = self.user_agent {
+            client.set_user_agent(&ua);
+        }
+
+        for (key, value) in self.headers {
+            client.add_header(&key, &value);
+        }
+
+        client.timeout = self.timeout;
+        client.runtime_fetch_timeout = self.runtime_fetch_timeout;
+        client.max_output_bytes = self.max_output_bytes;
+        client.retry_jitter = self.retry_jitter;
+
+        if let Some(CacheSnapshot {
+            ttl: Some(ttl),
+            fetched_at,
+            runtimes,
+        }) = self.cache
+        {
+            // An entry older than this process can't be an `Instant`,
+            // but it has expired anyway unless the ttl is very long.
+            let entry = fetched_at
+                .map(|t| SystemTime::now().duration_since(t).unwrap_or_default())
+                .and_then(|age| Instant::now().checked_sub(age))
+                .map(|fetched_at| (fetched_at, runtimes));
+
+            client.runtime_cache = Some(Arc::new(Mutex::new(RuntimeCache { ttl, entry })));
+        }
+
+        client
+    }
+}
+
+/// Builds the body streamed by [`Client::execute_large`]. Only the
+/// executor's other fields are serialized up front, the files are
+/// serialized as the body is sent.
+///
+/// # Arguments
+/// - `executor` - The executor, without its files.
+/// - `files` - The files of the executor.
+///
+/// # Returns
+/// - [`Result<reqwest::Body, PistonError>`] - The body, or the error
+///   if the executor could not be serialized.
+fn large_body(executor: &Executor, files: Vec<File>) -> Result<reqwest::Body, PistonError> {
+    let chunks = large_body_chunks(executor, files)?.map(Ok::<_, std::io::Error>);
+    Ok(reqwest::Body::wrap_stream(stream::iter(chunks)))
+}
+
+/// Splits the JSON body of an executor into chunks, serializing the
+/// content of each file [`LARGE_BODY_CHUNK_LEN`] bytes at a time.
+///
+/// # Arguments
+/// - `executor` - The executor, without its files.
+/// - `files` - The files of the executor.
+///
+/// # Returns
+/// - [`Result<impl Iterator<Item = Vec<u8>>, PistonError>`] - The
+///   chunks, or the error if the executor could not be serialized.
+fn large_body_chunks(
+    executor: &Executor,
+    files: Vec<File>,
+) -> Result<impl Iterator<Item = Vec<u8>> + Send + 'static, PistonError> {
+    let mut head = serde_json::to_value(executor)?;
+
+    if let Some(fields) = head.as_object_mut() {
+        fields.remove("files");
+    }
+
+    // Reopen the object, so the files can be appended to it.
+    let mut head = serde_json::to_vec(&head)?;
+    head.pop();
+    head.extend_from_slice(match head.len() {
+        1 => b"\"files\":[",
+        _ => b",\"files\":[",
+    });
+
+    let files = files.into_iter().enumerate().map(|(i, file)| {
+        let separator = if i == 0 { "" } else { "," };
+        let charset = match file.charset {
+            Some(charset) => format!(",\"charset\":{}", serde_json::Value::from(charset)),
+            None => String::new(),
+        };
+        let prefix = format!(
+            "{}{{\"name\":{},\"encoding\":{}{},\"content\":\"",
+            separator,
+            serde_json::Value::from(file.name),
+            serde_json::Value::from(file.encoding),
+            charset,
+        );
+
+        std::iter::once(prefix.into_bytes())
+            .chain(ContentChunks {
+                content: file.content,
+                offset: 0,
+            })
+            .chain(std::iter::once(b"\"}".to_vec()))
+    });
+
+    Ok(std::iter::once(head)
+        .chain(files.flatten())
+        .chain(std::iter::once(b"]}".to_vec())))
+}
+
+/// The content of a file, escaped as a JSON string one chunk at a
+/// time, without the surrounding quotes.
+struct ContentChunks {
+    /// The content of the file.
+    content: String,
+    /// How much of the content has been escaped so far.
+    offset: usize,
+}
+
+impl Iterator for ContentChunks {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.content.len() {
+            return None;
+        }
+
+        let mut end = (self.offset + LARGE_BODY_CHUNK_LEN).min(self.content.len());
+
+        while !self.content.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let mut escaped = serde_json::Value::from(&self.content[self.offset..end])
+            .to_string()
+            .into_bytes();
+        self.offset = end;
+
+        // Strip the surrounding quotes.
+        escaped.pop();
+        escaped.remove(0);
+        Some(escaped)
+    }
+}
+
+/// Whether or not the run step of a response was killed by its run
+/// timeout, rather than for exceeding its memory limit. The status
+/// and wall time of the run step are used when Piston sends them,
+/// otherwise the time the whole request took.
+///
+/// # Arguments
+/// - `response` - The response to check.
+/// - `run_timeout` - The run timeout the code was executed with, in
+///   milliseconds.
+///
+/// # Returns
+/// - [`bool`] - [`true`] if the run step timed out.
+fn killed_by_timeout(response: &ExecResponse, run_timeout: isize) -> bool {
+    let timeout = Duration::from_millis(run_timeout.max(0) as u64);
+    let run = &response.run;
+    let killed = run.signal_kind() == Some(Signal::Kill);
+
+    match (run.stage_status.as_deref(), run.wall_time) {
+        (Some("TO"), _) => true,
+        (_, Some(wall_time)) => killed && Duration::from_millis(wall_time) >= timeout,
+        (Some(_), None) => false,
+        (None, None) => killed && response.elapsed.is_some_and(|elapsed| elapsed >= timeout),
+    }
+}
+
+/// Picks a random duration between zero and `max`, for full jitter.
+///
+/// # Arguments
+/// - `max` - The longest duration to pick.
+///
+/// # Returns
+/// - [`Duration`] - The random duration.
+fn jitter(max: Duration) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+
+    // Each `RandomState` is seeded differently, which is random enough
+    // to spread out retries without a dependency on `rand`.
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u32(max.subsec_nanos());
+    let fraction = hasher.finish() as f64 / u64::MAX as f64;
+
+    max.mul_f64(fraction)
+}
+
+/// Removes any trailing slashes from the base url, so endpoints can be
+/// joined to it without producing a double slash.
+///
+/// # Arguments
+/// - `url` - The base url for Piston.
+///
+/// # Returns
+/// - [`String`] - The normalized url.
+pub(crate) fn normalize_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+/// Parses a single link from a `Link` header, if it is the link to
+/// the next page.
+///
+/// # Arguments
+/// - `link` - The link, i.e. `<https://example.com?page=2>; rel="next"`.
+///
+/// # Returns
+/// - [`Option<String>`] - The url of the next page, if this is it.
+fn next_link(link: &str) -> Option<String> {
+    let (url, params) = link.trim().split_once(';')?;
+    let is_next = params.split(';').any(|p| {
+        let p = p.trim().replace(' ', "");
+        p == "rel=\"next\"" || p == "rel=next"
+    });
+
+    match is_next {
+        true => Some(url.trim().strip_prefix('<')?.strip_suffix('>')?.to_string()),
+        false => None,
+    }
+}
+
+/// Merges the runtimes fetched from two Piston instances, i.e.
+/// mirrors with slightly different packages installed.
+///
+/// Runtimes are deduplicated by language and version. When both lists
+/// have the same runtime, the one from the list with more runtimes is
+/// kept, so its aliases are used. The result is sorted by language,
+/// then by version.
+///
+/// # Arguments
+/// - `a` - The runtimes from the first instance.
+/// - `b` - The runtimes from the second instance.
+///
+/// # Returns
+/// - [`Vec<Runtime>`] - The merged runtimes.
+///
+/// # Example
+/// ```
+/// let runtime = |language: &str, version: &str| piston_rs::Runtime {
+///     language: language.to_string(),
+///     version: version.to_string(),
+///     aliases: vec![],
+/// };
+///
+/// let merged = piston_rs::merge_runtimes(
+///     vec![runtime("python", "3.10.0"), runtime("bash", "5.2.0")],
+///     vec![runtime("python", "3.10.0"), runtime("python", "3.9.4")],
+/// );
+///
+/// assert_eq!(
+///     merged,
+///     vec![
+///         runtime("bash", "5.2.0"),
+///         runtime("python", "3.9.4"),
+///         runtime("python", "3.10.0"),
+///     ]
+/// );
+/// ```
+pub fn merge_runtimes(a: Vec<Runtime>, b: Vec<Runtime>) -> Vec<Runtime> {
+    let (preferred, other) = match b.len() > a.len() {
+        true => (b, a),
+        false => (a, b),
+    };
+
+    let mut seen = std::collections::HashSet::new();
+    let mut merged: Vec<_> = preferred
+        .into_iter()
+        .chain(other)
+        .filter(|rt| seen.insert((rt.language.clone(), rt.version.clone())))
+        .collect();
+
+    merged.sort_by(|x, y| {
+        x.language
+            .cmp(&y.language)
+            .then_with(|| compare_runtimes(x, y))
+    });
+    merged
+}
+
+/// Compares the versions of two runtimes, as semver where possible.
+/// Versions that are not valid semver are less than those that are,
+/// and are compared with [`compare_versions`] amongst themselves.
+///
+/// # Arguments
+/// - `a` - The first runtime.
+/// - `b` - The second runtime.
+///
+/// # Returns
+/// - [`Ordering`] - How `a` compares to `b`.
+fn compare_runtimes(a: &Runtime, b: &Runtime) -> Ordering {
+    a.version_semver()
+        .cmp(&b.version_semver())
+        .then_with(|| compare_versions(&a.version, &b.version))
+}
+
+/// Compares two dot separated version strings, numerically where
+/// possible.
+///
+/// # Arguments
+/// - `a` - The first version.
+/// - `b` - The second version.
+///
+/// # Returns
+/// - [`Ordering`] - How `a` compares to `b`.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (Some(x), Some(y)) => {
+                let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    _ => x.cmp(y),
+                };
+
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return Ordering::Equal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_client_private {
+    use super::compare_versions;
+    use super::jitter;
+    use super::killed_by_timeout;
+    use super::large_body_chunks;
+    use super::merge_runtimes;
+    use super::next_link;
+    use super::normalize_url;
+    use super::CacheSnapshot;
+    use super::Client;
+    use super::Executor;
+    use super::PistonError;
+    use super::RetryPolicy;
+    use super::Runtime;
+    use super::Signal;
+    use crate::File;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use std::cmp::Ordering;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    /// Builds a raw http response for the test server.
+    fn response(status: &str, headers: &[&str], body: &str) -> String {
+        let mut raw = format!("HTTP/1.1 {}\r\n", status);
+
+        for header in headers {
+            raw.push_str(&format!("{}\r\n", header));
+        }
+
+        raw.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        ));
+
+        raw
+    }
+
+    /// Serves each response, in order, to a new connection and returns
+    /// the url of the server.
+    fn serve(responses: Vec<String>) -> String {
+        serve_slowly(responses, Duration::ZERO)
+    }
+
+    /// Like `serve`, but waits for `delay` before each response.
+    fn serve_slowly(responses: Vec<String>, delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for raw in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 8192];
+                let _ = stream.read(&mut buf).unwrap();
+                std::thread::sleep(delay);
+                stream.write_all(raw.as_bytes()).unwrap();
+            }
+        });
+
+        url
+    }
+
+    /// Like `serve`, but also returns a handle to each request the
+    /// server received, in order. Responses may be any bytes, not just
+    /// text.
+    fn serve_capturing<R>(responses: Vec<R>) -> (String, JoinHandle<Vec<String>>)
+    where
+        R: AsRef<[u8]> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|raw| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let request = read_request(&mut stream);
+                    stream.write_all(raw.as_ref()).unwrap();
+                    request
+                })
+                .collect()
+        });
+
+        (url, handle)
+    }
+
+    /// Reads a whole request from the test server's connection,
+    /// including a body sent with `Content-Length`.
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut request = vec![];
+        let mut buf = [0; 8192];
+
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..n]);
+
+            let text = String::from_utf8_lossy(&request);
+            let complete = text.find("\r\n\r\n").is_some_and(|end| {
+                let len = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        match name.eq_ignore_ascii_case("content-length") {
+                            true => value.trim().parse::<usize>().ok(),
+                            false => None,
+                        }
+                    })
+                    .unwrap_or(0);
+
+                request.len() >= end + 4 + len
+            });
+
+            if complete || n == 0 {
+                return text.into_owned();
+            }
+        }
+    }
+
+    /// Accepts connections but never responds to them, and returns the
+    /// url of the server.
+    fn serve_hanging() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            // Keeps every connection open, so requests can only time out.
+            let mut connections = vec![];
+
+            for connection in listener.incoming() {
+                connections.push(connection);
+            }
+        });
+
+        url
+    }
+
+    /// A valid executor to send to the test server.
+    fn executor() -> Executor {
+        Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_content("print(42)"))
+    }
+
+    /// A successful execute response body.
+    const EXEC_BODY: &str = r#"{"language":"python","version":"3.10.0","run":{"stdout":"42\n","stderr":"","output":"42\n","code":0,"signal":null}}"#;
+
+    #[test]
+    fn test_gen_headers_no_key() {
+        let headers = Client::generate_headers(None);
+
+        assert!(!headers.contains_key("Authorization"));
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    }
+
+    #[test]
+    fn test_gen_headers_with_key() {
+        let headers = Client::generate_headers(Some("123abc"));
+
+        assert_eq!(headers.get("Authorization").unwrap(), "123abc");
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_timeout_elapses() {
+        let url = serve_hanging();
+
+        let client = Client::with_url(&url);
+        let executor = executor();
+        let result = client
+            .execute_with_timeout(&executor, Duration::from_millis(100))
+            .await;
+
+        match result.unwrap_err() {
+            err @ PistonError::Timeout { .. } => {
+                assert!(err.is_timeout());
+                assert!(err
+                    .to_string()
+                    .starts_with(&format!("Request to {}/execute timed out after", url)));
+            }
+            err => panic!("Expected a timeout error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::new();
+
+        assert_eq!(policy.delay(0), Duration::from_millis(250));
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(3), Duration::from_millis(2000));
+        assert!(policy.delay(u32::MAX) > Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_jitter() {
+        let max = Duration::from_millis(250);
+        let delays: Vec<_> = (0..20).map(|_| jitter(max)).collect();
+
+        assert!(delays.iter().all(|d| *d <= max));
+        assert!(delays.iter().any(|d| *d != delays[0]));
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_retry_succeeds() {
+        let url = serve(vec![
+            response("429 Too Many Requests", &["Retry-After: 0"], ""),
+            response("200 OK", &[], EXEC_BODY),
+        ]);
+
+        let client = Client::with_url(&url);
+        let executor = executor();
+        let response = client.execute_with_retry(&executor, 1).await.unwrap();
+
+        assert!(response.is_ok());
+        assert_eq!(response.run.stdout, "42\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_retry_exhausted() {
+        let url = serve(vec![
+            response("429 Too Many Requests", &["Retry-After: 0"], ""),
+            response("429 Too Many Requests", &["Retry-After: 0"], ""),
+        ]);
+
+        let client = Client::builder().url(&url).retry_jitter(false).build();
+        let executor = executor();
+        let result = client.execute_with_retry(&executor, 1).await;
+
+        assert!(matches!(
+            result,
+            Err(PistonError::RateLimited {
+                retry_after: Some(_)
+            })
+        ));
+    }
+
+    /// A runtimes response body.
+    const RUNTIMES_BODY: &str = r#"[{"language":"python","version":"3.10.0","aliases":["py"]}]"#;
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_cached() {
+        // Only one response is served, so a second request would fail.
+        let url = serve(vec![response("200 OK", &[], RUNTIMES_BODY)]);
+        let client = Client::with_url(&url).with_runtime_cache(Duration::from_secs(60));
+
+        let runtimes = client.fetch_runtimes().await.unwrap();
+        assert_eq!(runtimes[0].language, "python");
+
+        let cached = client.clone().fetch_runtimes().await.unwrap();
+        assert_eq!(cached[0].aliases, vec!["py".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_cache_expired() {
+        let url = serve(vec![
+            response("200 OK", &[], RUNTIMES_BODY),
+            response("200 OK", &[], "[]"),
+        ]);
+        let client = Client::with_url(&url).with_runtime_cache(Duration::ZERO);
+
+        assert_eq!(client.fetch_runtimes().await.unwrap().len(), 1);
+        assert!(client.fetch_runtimes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_cache() {
+        let url = serve(vec![response("200 OK", &[], RUNTIMES_BODY)]);
+        let client = Client::with_url(&url).with_runtime_cache(Duration::from_secs(60));
+        client.fetch_runtimes().await.unwrap();
+
+        let json = serde_json::to_string(&client.export_cache()).unwrap();
+        let snapshot: CacheSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.ttl, Some(Duration::from_secs(60)));
+        assert_eq!(snapshot.runtimes[0].language, "python");
+
+        // Nothing is served any more, so these must come from the cache.
+        let restored = Client::builder().url(&url).with_cache(snapshot).build();
+        let (runtimes, cached) = restored.fetch_runtimes_cached().await.unwrap();
+        assert!(cached);
+        assert_eq!(runtimes[0].aliases, vec!["py".to_string()]);
+    }
+
+    #[test]
+    fn test_with_cache_expired() {
+        let snapshot = CacheSnapshot {
+            ttl: Some(Duration::from_secs(60)),
+            fetched_at: std::time::SystemTime::now().checked_sub(Duration::from_secs(120)),
+            runtimes: vec![],
+        };
+        let client = Client::builder().with_cache(snapshot).build();
+        assert!(client.cached_runtimes().is_none());
+
+        let disabled = Client::new().export_cache();
+        assert_eq!(disabled.ttl, None);
+        let client = Client::builder().with_cache(disabled).build();
+        assert!(client.runtime_cache.is_none());
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.50.0", "1.50.0"), Ordering::Equal);
+        assert_eq!(compare_versions("3.10.0", "3.9.4"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0", "10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.1", "1.0"), Ordering::Greater);
+    }
+
+    #[tokio::test]
+    async fn test_get_runtime() {
+        let body = r#"[
+            {"language":"python","version":"3.9.4","aliases":["py"]},
+            {"language":"python","version":"3.10.0","aliases":["py"]},
+            {"language":"rust","version":"1.50.0","aliases":["rs"]}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body); 3]);
+        let client = Client::with_url(&url);
+
+        let latest = client.get_runtime("py", "*").await.unwrap().unwrap();
+        assert_eq!(latest.version, "3.10.0");
+
+        let exact = client
+            .get_runtime("python", "3.9.4")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exact.version, "3.9.4");
+
+        assert!(client.get_runtime("rust", "2.0.0").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execute_invalid_executor() {
+        // Nothing is listening here, so any request would fail.
+        let client = Client::with_url("http://127.0.0.1:1");
+        let result = client.execute(&Executor::new()).await;
+
+        assert!(matches!(result, Err(PistonError::InvalidExecutor(_))));
+    }
+
+    #[tokio::test]
+    async fn test_execute_snippet() {
+        let url = serve(vec![response("200 OK", &[], EXEC_BODY)]);
+        let client = Client::with_url(&url);
+        let response = client
+            .execute_snippet("python", "3.10.0", "print(42)")
+            .await
+            .unwrap();
+
+        assert_eq!(response.language, "python");
+        assert_eq!(response.run.stdout, "42\n");
+        assert!(response.elapsed.is_some());
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let client = Client::builder().build();
+        let default = Client::new();
+
+        assert_eq!(client.get_url(), default.get_url());
+        assert_eq!(client.get_headers(), default.get_headers());
+        assert_eq!(client.timeout, None);
+    }
+
+    #[test]
+    fn test_builder() {
+        let client = Client::builder()
+            .url("http://localhost:3000")
+            .key("123abc")
+            .timeout(Duration::from_secs(5))
+            .user_agent("my-bot/1.2.3")
+            .build();
+
+        let headers = client.get_headers();
+        assert_eq!(client.get_url(), "http://localhost:3000");
+        assert_eq!(headers.get("Authorization").unwrap(), "123abc");
+        assert_eq!(headers.get("User-Agent").unwrap(), "my-bot/1.2.3");
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(client.timeout, Some(Duration::from_secs(5)));
+    }
+
+    #[tokio::test]
+    async fn test_builder_timeout_elapses() {
+        let url = serve_hanging();
+
+        let client = Client::builder()
+            .url(&url)
+            .timeout(Duration::from_millis(100))
+            .build();
+
+        assert!(client.fetch_runtimes().await.unwrap_err().is_timeout());
+    }
+
+    #[test]
+    fn test_set_and_clear_key() {
+        let mut client = Client::new();
+        client.set_key("123abc");
+        client.set_key("456def");
+
+        let headers = client.get_headers();
+        assert_eq!(headers.get("Authorization").unwrap(), "456def");
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+
+        client.clear_key();
+
+        let headers = client.get_headers();
+        assert!(!headers.contains_key("Authorization"));
+        assert_eq!(headers.get("Accept").unwrap(), "application/json");
+        assert_eq!(headers.get("User-Agent").unwrap(), "piston-rs");
+    }
+
+    #[tokio::test]
+    async fn test_execute_many() {
+        let url = serve(vec![response("200 OK", &[], EXEC_BODY); 3]);
+        let client = Client::with_url(&url);
+        let executors = vec![executor(), Executor::new(), executor(), executor()];
+        let results = client
+            .execute_many(&executors, 2, &RetryPolicy::new())
+            .await;
+
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(PistonError::InvalidExecutor(_))));
+        assert!(results[2].is_ok());
+        assert!(results[3].is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_many_rate_limited() {
+        let url = serve(vec![
+            response("429 Too Many Requests", &[], "Slow down"),
+            response("200 OK", &[], EXEC_BODY),
+            response("200 OK", &[], EXEC_BODY),
+        ]);
+        let client = Client::builder().url(&url).retry_jitter(false).build();
+        let policy = RetryPolicy::new().base_delay(Duration::ZERO);
+        let results = client
+            .execute_many(&[executor(), executor()], 1, &policy)
+            .await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn test_supported_languages() {
+        let body = r#"[
+            {"language":"python","version":"3.9.4","aliases":["py"]},
+            {"language":"python","version":"3.10.0","aliases":["py","py3"]},
+            {"language":"bash","version":"5.1.0","aliases":[]}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body)]);
+        let client = Client::with_url(&url);
+
+        assert_eq!(
+            client.supported_languages().await.unwrap(),
+            vec!["bash", "py", "py3", "python"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_version() {
+        let body = r#"[
+            {"language":"python","version":"3.9.4","aliases":["py"]},
+            {"language":"python","version":"3.10.0","aliases":["py"]}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body); 2]);
+        let client = Client::with_url(&url);
+
+        let mut executor = executor();
+        client.resolve_version(&mut executor).await.unwrap();
+        assert_eq!(executor.version, "3.10.0");
+
+        let mut executor = executor.set_version("3.9.4");
+        client.resolve_version(&mut executor).await.unwrap();
+        assert_eq!(executor.version, "3.9.4");
+
+        let mut executor = Executor::new().set_language("cobol");
+        let err = client.resolve_version(&mut executor).await.unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown language: cobol");
+    }
+
+    #[tokio::test]
+    async fn test_builder_proxy() {
+        // The proxy answers instead of the unreachable Piston url.
+        let proxy = serve(vec![response("200 OK", &[], RUNTIMES_BODY)]);
+        let client = Client::builder()
+            .url("http://piston.invalid")
+            .proxy(reqwest::Proxy::http(&proxy).unwrap())
+            .build();
+
+        let runtimes = client.fetch_runtimes().await.unwrap();
+        assert_eq!(runtimes[0].language, "python");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_packages() {
+        let body = r#"[
+            {"language":"python","language_version":"3.10.0","installed":true},
+            {"language":"rust","language_version":"1.68.2","installed":false}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body)]);
+        let client = Client::with_url(&url);
+        let packages = client.fetch_packages().await.unwrap();
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[1].language, "rust");
+        assert_eq!(packages[1].language_version, "1.68.2");
+        assert!(packages[0].installed);
+        assert!(!packages[1].installed);
+    }
+
+    #[tokio::test]
+    async fn test_installed_and_available_packages() {
+        let body = r#"[
+            {"language":"python","language_version":"3.10.0","installed":true},
+            {"language":"rust","language_version":"1.68.2","installed":false}
+        ]"#;
+        let url = serve(vec![
+            response("200 OK", &[], body),
+            response("200 OK", &[], body),
+        ]);
+        let client = Client::with_url(&url);
+
+        let installed = client.installed_packages().await.unwrap();
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].language, "python");
+
+        let available = client.available_packages().await.unwrap();
+        assert_eq!(available.len(), 1);
+        assert_eq!(available[0].language, "rust");
+    }
+
+    #[tokio::test]
+    async fn test_install_package() {
+        let body = r#"{"language":"python","version":"3.10.0"}"#;
+        let url = serve(vec![response("200 OK", &[], body)]);
+        let client = Client::with_url(&url);
+        let package = client.install_package("python", "3.10.0").await.unwrap();
+
+        assert_eq!(package.language, "python");
+        assert_eq!(package.language_version, "3.10.0");
+        assert!(package.installed);
+    }
+
+    #[tokio::test]
+    async fn test_install_package_unavailable() {
+        let url = serve(vec![
+            response("403 Forbidden", &[], ""),
+            response("404 Not Found", &[], ""),
+        ]);
+        let client = Client::with_url(&url);
+
+        for status in [403, 404] {
+            let err = client
+                .install_package("python", "3.10.0")
+                .await
+                .unwrap_err();
+            assert!(matches!(err, PistonError::PackagesUnavailable { status: s } if s == status));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_uninstall_package() {
+        let body = r#"{"language":"python","version":"3.10.0"}"#;
+        let not_installed = r#"{"message":"python-3.10.0 not installed"}"#;
+        let invalid = r#"{"message":"language is required"}"#;
+        let url = serve(vec![
+            response("200 OK", &[], body),
+            response("400 Bad Request", &[], not_installed),
+            response("400 Bad Request", &[], invalid),
+        ]);
+        let client = Client::with_url(&url);
+
+        let package = client.uninstall_package("python", "3.10.0").await.unwrap();
+        assert_eq!(package.language_version, "3.10.0");
+        assert!(!package.installed);
+
+        let err = client
+            .uninstall_package("python", "3.10.0")
+            .await
+            .unwrap_err();
+        assert_eq!(format!("{}", err), "Package python 3.10.0 is not installed");
+
+        let err = client
+            .uninstall_package("python", "3.10.0")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PistonError::Api { status: 400, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_raw() {
+        let url = serve(vec![
+            response("200 OK", &["X-Request-Id: abc"], EXEC_BODY),
+            response("500 Internal Server Error", &[], "Oops"),
+        ]);
+        let client = Client::with_url(&url);
+
+        let raw = client.execute_raw(&executor()).await.unwrap();
+        assert_eq!(raw.headers().get("X-Request-Id").unwrap(), "abc");
+        assert!(raw.text().await.unwrap().contains("\"stdout\":\"42\\n\""));
+
+        let raw = client.execute_raw(&executor()).await.unwrap();
+        assert_eq!(raw.status(), 500);
+
+        let err = client.execute_raw(&Executor::new()).await.unwrap_err();
+        assert!(matches!(err, PistonError::InvalidExecutor(_)));
+    }
+
+    #[tokio::test]
+    async fn test_last_rate_limit() {
+        let url = serve(vec![
+            response("200 OK", &[], RUNTIMES_BODY),
+            response(
+                "200 OK",
+                &[
+                    "X-RateLimit-Limit: 5",
+                    "X-RateLimit-Remaining: 4",
+                    "X-RateLimit-Reset: 1",
+                ],
+                EXEC_BODY,
+            ),
+            response("429 Too Many Requests", &["X-RateLimit-Remaining: 0"], ""),
+        ]);
+        let client = Client::with_url(&url);
+
+        client.fetch_runtimes().await.unwrap();
+        assert_eq!(client.last_rate_limit(), None);
+
+        client.execute(&executor()).await.unwrap();
+        let rate_limit = client.clone().last_rate_limit().unwrap();
+        assert_eq!(rate_limit.limit, Some(5));
+        assert_eq!(rate_limit.remaining, Some(4));
+        assert_eq!(rate_limit.reset, Some(1));
+
+        client.execute(&executor()).await.unwrap_err();
+        assert_eq!(client.last_rate_limit().unwrap().remaining, Some(0));
+        assert_eq!(client.last_rate_limit().unwrap().limit, None);
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let url = serve(vec![
+            response("200 OK", &[], RUNTIMES_BODY),
+            response("503 Service Unavailable", &[], "Down"),
+        ]);
+        let client = Client::with_url(&url);
+
+        assert!(client.ping().await.unwrap() < Duration::from_secs(5));
+
+        let err = client.ping().await.unwrap_err();
+        assert!(matches!(err, PistonError::Api { status: 503, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_latest_runtime() {
+        let body = r#"[
+            {"language":"python","version":"3.10.0","aliases":["py"]},
+            {"language":"python","version":"3.10.0-rc1","aliases":["py"]},
+            {"language":"python","version":"3.9.4","aliases":["py"]},
+            {"language":"python","version":"2022.1","aliases":["py"]},
+            {"language":"rust","version":"nightly","aliases":[]},
+            {"language":"rust","version":"beta","aliases":[]}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body); 3]);
+        let client = Client::with_url(&url);
+
+        let python = client.latest_runtime("py").await.unwrap().unwrap();
+        assert_eq!(python.version, "3.10.0");
+
+        let rust = client.latest_runtime("rust").await.unwrap().unwrap();
+        assert_eq!(rust.version, "nightly");
+
+        assert!(client.latest_runtime("cobol").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_language() {
+        let body = r#"[
+            {"language":"python","version":"3.10.0","aliases":["py"]},
+            {"language":"py","version":"0.1.0","aliases":[]},
+            {"language":"javascript","version":"18.15.0","aliases":["js"]}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body); 4]);
+        let client = Client::with_url(&url);
+
+        let mut executor = executor().set_language("JS");
+        client.resolve_language(&mut executor).await.unwrap();
+        assert_eq!(executor.language, "javascript");
+
+        // "py" is a runtime in its own right, so it is left alone.
+        let mut executor = executor.set_language("py");
+        client.resolve_language(&mut executor).await.unwrap();
+        assert_eq!(executor.language, "py");
+
+        let mut executor = executor.set_version("3.10.0");
+        client.resolve_language(&mut executor).await.unwrap();
+        assert_eq!(executor.language, "python");
+
+        let mut executor = executor.set_language("cobol");
+        let err = client.resolve_language(&mut executor).await.unwrap_err();
+        assert!(matches!(err, PistonError::UnknownLanguage { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_set_timeout_elapses() {
+        let url = serve_hanging();
+
+        let mut client = Client::with_url(&url);
+        client.set_timeout(Duration::from_millis(100));
+
+        assert!(client.execute(&executor()).await.unwrap_err().is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_max_output_bytes() {
+        let url = serve(vec![response("200 OK", &[], EXEC_BODY); 2]);
+        let small = Client::builder()
+            .url(&url)
+            .max_output_bytes(EXEC_BODY.len() - 1)
+            .build();
+        let large = Client::builder()
+            .url(&url)
+            .max_output_bytes(EXEC_BODY.len())
+            .build();
+
+        let err = small.execute(&executor()).await.unwrap_err();
+        assert!(
+            matches!(err, PistonError::OutputTooLarge { limit } if limit == EXEC_BODY.len() - 1)
+        );
+        assert!(large.execute(&executor()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_default_versions() {
+        let body = r#"[
+            {"language":"python","version":"3.9.4","aliases":["py"]},
+            {"language":"python","version":"3.10.0","aliases":["py","py3"]},
+            {"language":"bash","version":"5.1.0","aliases":[]}
+        ]"#;
+        let url = serve(vec![response("200 OK", &[], body)]);
+        let client = Client::with_url(&url);
+        let versions = client.default_versions().await.unwrap();
+
+        assert_eq!(versions.len(), 4);
+        assert_eq!(versions["python"], "3.10.0");
+        assert_eq!(versions["py"], "3.10.0");
+        assert_eq!(versions["py3"], "3.10.0");
+        assert_eq!(versions["bash"], "5.1.0");
+    }
+
+    #[tokio::test]
+    async fn test_builder_connection_options() {
+        let url = serve(vec![response("200 OK", &[], RUNTIMES_BODY)]);
+        let client = Client::builder()
+            .url(&url)
+            .pool_max_idle_per_host(0)
+            .tcp_keepalive(Duration::from_secs(30))
+            .build();
+
+        assert!(client.fetch_runtimes().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_execute_unknown_language() {
+        let body = r#"{"message":"cobol-* runtime is unknown"}"#;
+        let url = serve(vec![response("400 Bad Request", &[], body)]);
+        let client = Client::with_url(&url);
+        let err = client
+            .execute(&executor().set_language("cobol"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PistonError::UnknownLanguage { language } if language == "cobol"));
+    }
+
+    #[test]
+    fn test_next_link() {
+        assert_eq!(
+            next_link(r#"<http://localhost/runtimes?page=2>; rel="next""#),
+            Some("http://localhost/runtimes?page=2".to_string())
+        );
+        assert_eq!(next_link(r#"</runtimes?page=1>; rel="prev""#), None);
+        assert_eq!(next_link("garbage"), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_stream() {
+        use futures_util::StreamExt;
+
+        let first = r#"[{"language":"bash","version":"5.1.0","aliases":[]}]"#;
+        let second = r#"[{"language":"python","version":"3.10.0","aliases":[]}]"#;
+        let url = serve(vec![
+            response(
+                "200 OK",
+                &[r#"Link: </runtimes?page=2>; rel="next""#],
+                first,
+            ),
+            response("200 OK", &[], second),
+        ]);
+        let client = Client::with_url(&url);
+
+        let runtimes: Vec<_> = client.fetch_runtimes_stream().collect().await;
+        let languages: Vec<_> = runtimes
+            .into_iter()
+            .map(|rt| rt.unwrap().language)
+            .collect();
+
+        assert_eq!(languages, vec!["bash", "python"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_stream_cycle() {
+        use futures_util::StreamExt;
+
+        let first = r#"[{"language":"bash","version":"5.1.0","aliases":[]}]"#;
+        let second = r#"[{"language":"python","version":"3.10.0","aliases":[]}]"#;
+        let url = serve(vec![
+            response(
+                "200 OK",
+                &[r#"Link: </runtimes?page=2>; rel="next""#],
+                first,
+            ),
+            response("200 OK", &[r#"Link: </runtimes>; rel="next""#], second),
+        ]);
+        let client = Client::with_url(&url);
+
+        let runtimes: Vec<_> = client.fetch_runtimes_stream().collect().await;
+        let languages: Vec<_> = runtimes
+            .into_iter()
+            .map(|rt| rt.unwrap().language)
+            .collect();
+
+        assert_eq!(languages, vec!["bash", "python"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_stream_error() {
+        use futures_util::StreamExt;
+
+        let url = serve(vec![response("500 Internal Server Error", &[], "Oops")]);
+        let client = Client::with_url(&url);
+        let runtimes: Vec<_> = client.fetch_runtimes_stream().collect().await;
+
+        assert_eq!(runtimes.len(), 1);
+        assert!(matches!(
+            runtimes[0],
+            Err(PistonError::Api { status: 500, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_can_execute() {
+        let url = serve(vec![
+            response("200 OK", &["X-RateLimit-Remaining: 1"], EXEC_BODY),
+            response(
+                "429 Too Many Requests",
+                &["X-RateLimit-Remaining: 0", "X-RateLimit-Reset: 60"],
+                "",
+            ),
+            response(
+                "429 Too Many Requests",
+                &["X-RateLimit-Remaining: 0", "X-RateLimit-Reset: 86401"],
+                "",
+            ),
+        ]);
+        let client = Client::with_url(&url);
+        assert!(client.can_execute());
+
+        client.execute(&executor()).await.unwrap();
+        assert!(client.can_execute());
+
+        client.execute(&executor()).await.unwrap_err();
+        assert!(!client.can_execute());
+
+        // A timestamp long in the past.
+        client.execute(&executor()).await.unwrap_err();
+        assert!(client.can_execute());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_headers() {
+        let (url, handle) = serve_capturing(vec![response("200 OK", &[], EXEC_BODY)]);
+        let client = Client::with_url(&url);
+        let mut extra = HeaderMap::new();
+        extra.insert("X-Correlation-Id", HeaderValue::from_static("abc123"));
+        extra.insert("User-Agent", HeaderValue::from_static("scheduler"));
+
+        client
+            .execute_with_headers(&executor(), extra)
+            .await
+            .unwrap();
+
+        let request = &handle.join().unwrap()[0];
+        assert!(request.contains("x-correlation-id: abc123"));
+        assert!(request.contains("user-agent: scheduler"));
+        assert!(!request.contains("user-agent: piston-rs"));
+        assert!(!client.get_headers().contains_key("X-Correlation-Id"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_auth() {
+        let (url, handle) = serve_capturing(vec![response("200 OK", &[], EXEC_BODY); 2]);
+
+        let mut client = Client::with_key("123abc");
+        client.set_url(&url);
+
+        client.execute_without_auth(&executor()).await.unwrap();
+        client.execute(&executor()).await.unwrap();
+
+        let requests = handle.join().unwrap();
+        assert!(!requests[0].contains("authorization"));
+        assert!(requests[1].contains("authorization: 123abc"));
+        assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let client = Client::with_url_and_key("http://localhost", "123abc");
+        let request = client.dry_run(&executor());
+        let (head, body) = request.split_once("\n\n").unwrap();
+
+        assert!(head.starts_with("POST http://localhost/execute\n"));
+        assert!(head.contains("accept: application/json"));
+        assert!(head.contains("authorization: <redacted>"));
+        assert!(!request.contains("123abc"));
+        assert_eq!(Executor::from_json_str(body).unwrap(), executor());
+    }
+
+    #[tokio::test]
+    async fn test_runtime_fetch_timeout() {
+        let url = serve_hanging();
+
+        let client = Client::builder()
+            .url(&url)
+            .timeout(Duration::from_secs(60))
+            .runtime_fetch_timeout(Duration::from_millis(100))
+            .build();
+
+        assert!(client.fetch_runtimes().await.unwrap_err().is_timeout());
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let client = Client::from_vars(None, Some(String::new())).unwrap();
+        assert_eq!(client.get_url(), "https://emkc.org/api/v2/piston");
+        assert!(!client.get_headers().contains_key("Authorization"));
+
+        let client = Client::from_vars(
+            Some("http://localhost:2000/api/v2".to_string()),
+            Some("123abc".to_string()),
+        )
+        .unwrap();
+        assert_eq!(client.get_url(), "http://localhost:2000/api/v2");
+        assert_eq!(client.get_headers().get("Authorization").unwrap(), "123abc");
+    }
+
+    #[test]
+    fn test_from_vars_malformed() {
+        let err = Client::from_vars(Some("localhost".to_string()), None).unwrap_err();
+        assert!(matches!(err, PistonError::InvalidConfig(_)));
+        assert!(err
+            .to_string()
+            .starts_with("Invalid configuration: PISTON_URL"));
+
+        let err = Client::from_vars(Some("ftp://localhost".to_string()), None).unwrap_err();
+        assert!(matches!(err, PistonError::InvalidConfig(_)));
+
+        let err = Client::from_vars(None, Some("123\nabc".to_string())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid configuration: PISTON_API_KEY: invalid header value"
+        );
+    }
+
+    #[test]
+    fn test_merge_runtimes_prefers_larger() {
+        let runtime = |version: &str, aliases: &[&str]| Runtime {
+            language: "python".to_string(),
+            version: version.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        };
+
+        let merged = merge_runtimes(
+            vec![runtime("3.10.0", &["py"])],
+            vec![runtime("3.10.0", &["py3"]), runtime("2.7.18", &[])],
+        );
+
+        assert_eq!(
+            merged,
+            vec![runtime("2.7.18", &[]), runtime("3.10.0", &["py3"])]
+        );
+        assert!(merge_runtimes(vec![], vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("http://localhost:3000/"),
+            "http://localhost:3000"
+        );
+        assert_eq!(
+            normalize_url("http://localhost:3000//"),
+            "http://localhost:3000"
+        );
+        assert_eq!(
+            normalize_url("http://localhost:3000"),
+            "http://localhost:3000"
+        );
+    }
+
+    #[test]
+    fn test_trailing_slash_endpoint() {
+        let with = Client::with_url("http://localhost:3000/api/v2/");
+        let without = Client::builder()
+            .url("http://localhost:3000/api/v2")
+            .build();
+
+        assert_eq!(with.get_url(), without.get_url());
+        assert_eq!(with.dry_run(&executor()), without.dry_run(&executor()));
+        assert!(with
+            .dry_run(&executor())
+            .starts_with("POST http://localhost:3000/api/v2/execute\n"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes_cache_hit_flag() {
+        let url = serve(vec![
+            response("200 OK", &[], RUNTIMES_BODY),
+            response("200 OK", &[], RUNTIMES_BODY),
+        ]);
+        let client = Client::with_url(&url).with_runtime_cache(Duration::from_secs(60));
+
+        let (runtimes, cached) = client.fetch_runtimes_cached().await.unwrap();
+        assert_eq!(runtimes.len(), 1);
+        assert!(!cached);
+
+        let (cached_runtimes, cached) = client.fetch_runtimes_cached().await.unwrap();
+        assert_eq!(cached_runtimes, runtimes);
+        assert!(cached);
+
+        let uncached = Client::with_url(&url);
+        assert!(!uncached.fetch_runtimes_cached().await.unwrap().1);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_accept_encoding_gzip() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(RUNTIMES_BODY.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut raw = format!(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        raw.extend(body);
+
+        let (url, handle) = serve_capturing(vec![raw]);
+        let runtimes = Client::with_url(&url).fetch_runtimes().await.unwrap();
+
+        let request = &handle.join().unwrap()[0];
+        assert!(request.contains("accept-encoding: gzip"));
+        assert_eq!(runtimes[0].language, "python");
+    }
+
+    #[test]
+    fn test_large_body_chunks() {
+        let content = "\"é\"\n".repeat(super::LARGE_BODY_CHUNK_LEN);
+        let executor = Executor::new()
+            .set_language("python")
+            .set_stdin("42")
+            .add_file(File::default().set_name("main.py").set_content("print(42)"))
+            .add_file(File::default().set_name("data.txt").set_content(&content))
+            .add_file(File::from("データ").set_charset("Shift_JIS"));
+
+        let mut head = executor.clone();
+        let files = std::mem::take(&mut head.files);
+        let chunks: Vec<_> = large_body_chunks(&head, files).unwrap().collect();
+        let body: Vec<u8> = chunks.concat();
+
+        assert!(chunks.len() > 4);
+        assert!(chunks
+            .iter()
+            .all(|c| c.len() <= super::LARGE_BODY_CHUNK_LEN * 2));
+        assert_eq!(
+            Executor::from_json_str(std::str::from_utf8(&body).unwrap()).unwrap(),
+            executor
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_large() {
+        let url = serve(vec![response("200 OK", &[], EXEC_BODY)]);
+        let client = Client::with_url(&url);
+        let response = client.execute_large(executor()).await.unwrap();
+
+        assert_eq!(response.run.stdout, "42\n");
+        assert!(response.elapsed.is_some());
+
+        let err = client.execute_large(Executor::new()).await.unwrap_err();
+        assert!(matches!(err, PistonError::InvalidExecutor(_)));
+    }
+
+    #[test]
+    fn test_basic_auth_with_key_header() {
+        let client = Client::builder()
+            .key("123abc")
+            .key_header("X-Api-Key")
+            .basic_auth("user", "pass")
+            .build();
+        let request = client.dry_run(&executor());
+
+        assert_eq!(
+            client.get_headers().get("Authorization").unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+        assert!(request.contains("x-api-key: <redacted>"));
+        assert!(!request.contains("123abc"));
+
+        let mut client = Client::builder()
+            .key("123abc")
+            .basic_auth("user", "pass")
+            .build();
+        assert_eq!(
+            client.get_headers().get("Authorization").unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+
+        // There's no header left for the key, so basic auth is kept.
+        client.set_key("456def");
+        client.clear_key();
+        assert_eq!(
+            client.get_headers().get("Authorization").unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_auth_key_header() {
+        let (url, handle) = serve_capturing(vec![response("200 OK", &[], EXEC_BODY); 2]);
+        let mut client = Client::builder()
+            .url(&url)
+            .key("123abc")
+            .key_header("X-Api-Key")
+            .basic_auth("user", "pass")
+            .build();
+
+        client.execute_without_auth(&executor()).await.unwrap();
+        client.set_key("456def");
+        client.execute(&executor()).await.unwrap();
+
+        let requests = handle.join().unwrap();
+        assert!(!requests[0].contains("x-api-key"));
+        assert!(requests[0].contains("authorization: Basic dXNlcjpwYXNz"));
+        assert!(requests[1].contains("x-api-key: 456def"));
+        assert!(requests[1].contains("authorization: Basic dXNlcjpwYXNz"));
+
+        client.clear_key();
+        assert!(!client.get_headers().contains_key("X-Api-Key"));
+        assert!(client.get_headers().contains_key("Authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_policy() {
+        let url = serve(vec![
+            response("503 Service Unavailable", &[], "Down"),
+            response("502 Bad Gateway", &[], "Down"),
+            response("200 OK", &[], EXEC_BODY),
+            response("500 Internal Server Error", &[], "Oops"),
+        ]);
+        let client = Client::builder().url(&url).retry_jitter(false).build();
+        let policy = RetryPolicy::new().base_delay(Duration::ZERO);
+
+        let response = client.execute_with_policy(&executor(), &policy).await;
+        assert_eq!(response.unwrap().run.stdout, "42\n");
+
+        let err = client.execute_with_policy(&executor(), &policy).await;
+        assert!(matches!(err, Err(PistonError::Api { status: 500, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_policy_connection_error() {
+        // Nothing is listening once the listener is dropped.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let client = Client::with_url(&url);
+        let policy = RetryPolicy::new()
+            .max_retries(1)
+            .base_delay(Duration::from_millis(1));
+        let err = client.execute_with_policy(&executor(), &policy).await;
+
+        match err {
+            Err(PistonError::Http(e)) => assert!(e.is_connect()),
+            other => panic!("Expected a connection error, got {:?}", other),
+        }
+    }
+
+    /// An execute response body for a run killed with `SIGKILL`.
+    const KILLED_BODY: &str = r#"{"language":"python","version":"3.10.0","run":{"stdout":"","stderr":"","output":"","code":137,"signal":"SIGKILL"}}"#;
+
+    #[test]
+    fn test_killed_by_timeout() {
+        let body = KILLED_BODY.to_string();
+        let mut response =
+            Client::parse_execute(reqwest::StatusCode::OK, &HeaderMap::new(), body).unwrap();
+        response.elapsed = Some(Duration::from_millis(3000));
+
+        assert!(killed_by_timeout(&response, 3000));
+        assert!(!killed_by_timeout(&response, 5000));
+
+        response.run.signal = None;
+        assert!(!killed_by_timeout(&response, 3000));
+
+        response.elapsed = None;
+        response.run.signal = Some("SIGKILL".to_string());
+        assert!(!killed_by_timeout(&response, 3000));
+    }
+
+    #[test]
+    fn test_killed_by_timeout_stage_fields() {
+        let body = r#"{"language":"rust","version":"1.68.2","compile":{"stdout":"","stderr":"","output":"","code":0,"signal":null},"run":{"stdout":"","stderr":"","output":"","code":137,"signal":"SIGKILL","status":"SG","message":"Memory limit exceeded","wall_time":12}}"#;
+        let mut response =
+            Client::parse_execute(reqwest::StatusCode::OK, &HeaderMap::new(), body.to_string())
+                .unwrap();
+
+        // A long compile made the request slower than the run timeout.
+        response.elapsed = Some(Duration::from_millis(9000));
+        assert_eq!(
+            response.run.message.as_deref(),
+            Some("Memory limit exceeded")
+        );
+        assert!(!killed_by_timeout(&response, 3000));
+
+        response.run.wall_time = Some(3004);
+        assert!(killed_by_timeout(&response, 3000));
+
+        response.run.stage_status = Some("TO".to_string());
+        response.run.wall_time = None;
+        assert!(killed_by_timeout(&response, 3000));
+
+        response.run.stage_status = Some("SG".to_string());
+        assert!(!killed_by_timeout(&response, 3000));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_adaptive_timeout() {
+        let url = serve_slowly(
+            vec![
+                response("200 OK", &[], KILLED_BODY),
+                response("200 OK", &[], KILLED_BODY),
+                response("200 OK", &[], EXEC_BODY),
+                response("200 OK", &[], KILLED_BODY),
+                response("200 OK", &[], KILLED_BODY),
+            ],
+            Duration::from_millis(20),
+        );
+        let client = Client::with_url(&url);
+
+        let ok = client
+            .execute_with_adaptive_timeout(&executor(), 2, 8)
+            .await;
+        assert_eq!(ok.unwrap().run.stdout, "42\n");
+
+        // The second attempt already uses the maximum, so it is the last.
+        let killed = client
+            .execute_with_adaptive_timeout(&executor(), 4, 8)
+            .await;
+        assert_eq!(killed.unwrap().run.signal_kind(), Some(Signal::Kill));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_adaptive_timeout_initial_above_max() {
+        let body = KILLED_BODY.replace(r#""signal""#, r#""status":"TO","signal""#);
+        let (url, handle) = serve_capturing(vec![response("200 OK", &[], &body)]);
+
+        let client = Client::with_url(&url);
+        let response = client
+            .execute_with_adaptive_timeout(&executor(), 20000, 5000)
+            .await
+            .unwrap();
+
+        assert_eq!(response.run.stage_status.as_deref(), Some("TO"));
+        assert!(handle.join().unwrap()[0].contains(r#""run_timeout":5000"#));
+
+        for (initial, max) in [(0, 5000), (1000, -1)] {
+            let err = client
+                .execute_with_adaptive_timeout(&executor(), initial, max)
+                .await;
+            assert!(matches!(err, Err(PistonError::InvalidExecutor(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_adaptive_timeout_killed_early() {
+        // Only one response is served, so a retry would fail.
+        let url = serve(vec![response("200 OK", &[], KILLED_BODY)]);
+        let client = Client::with_url(&url);
+        let response = client
+            .execute_with_adaptive_timeout(&executor(), 60000, 120000)
+            .await;
+
+        assert_eq!(response.unwrap().run.signal_kind(), Some(Signal::Kill));
     }
 }
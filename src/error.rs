@@ -0,0 +1,563 @@
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::StatusCode;
+
+/// The maximum number of characters of a non JSON error body that are
+/// kept in the error message.
+const MAX_ERROR_BODY_LEN: usize = 200;
+
+/// The error that is returned when a request to Piston fails for any
+/// reason.
+#[derive(Debug)]
+pub enum PistonError {
+    /// The http request itself failed, i.e. Piston could not be
+    /// reached or the connection was interrupted.
+    Http(reqwest::Error),
+    /// Piston did not respond to an execution before the timeout
+    /// elapsed. Piston sends the output all at once, so none of it is
+    /// available.
+    Timeout {
+        /// The endpoint the request was sent to.
+        endpoint: String,
+        /// How long the request ran for before timing out.
+        elapsed: Duration,
+    },
+    /// A streaming execution did not complete before the timeout
+    /// elapsed. Unlike [`PistonError::Timeout`], the output received
+    /// before the timeout is kept.
+    #[cfg(feature = "ws")]
+    StreamTimeout {
+        /// The endpoint the connection was made to.
+        endpoint: String,
+        /// How long the execution ran for before timing out.
+        elapsed: Duration,
+        /// The output received before the timeout, in order.
+        partial: Vec<crate::OutputChunk>,
+    },
+    /// Piston rate limited the request (HTTP 429).
+    RateLimited {
+        /// How long Piston asked us to wait before trying again, if it
+        /// said.
+        retry_after: Option<Duration>,
+    },
+    /// Piston responded with a non 200 status code.
+    Api {
+        /// The status code returned by Piston.
+        status: u16,
+        /// The message returned by Piston.
+        message: String,
+    },
+    /// The WebSocket connection to Piston failed.
+    #[cfg(feature = "ws")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    /// The response from Piston could not be deserialized.
+    Deserialize(serde_json::Error),
+    /// The response from Piston was larger than the maximum set with
+    /// [`crate::ClientBuilder::max_output_bytes`].
+    OutputTooLarge {
+        /// The maximum size of a response body, in bytes.
+        limit: usize,
+    },
+    /// The executor is missing something Piston requires, so it was
+    /// not sent.
+    InvalidExecutor(String),
+    /// The configuration for the client is invalid, i.e. an
+    /// environment variable read by [`crate::Client::from_env`] is
+    /// malformed.
+    InvalidConfig(String),
+    /// The package endpoints are not available on this Piston
+    /// instance (HTTP 403 or 404). They are only exposed by self-hosted
+    /// instances.
+    PackagesUnavailable {
+        /// The status code returned by Piston.
+        status: u16,
+    },
+    /// The package to uninstall is not installed on Piston.
+    PackageNotInstalled {
+        /// The language of the package.
+        language: String,
+        /// The version of the package.
+        version: String,
+    },
+    /// Piston has no runtime for the requested language.
+    UnknownLanguage {
+        /// The language that was requested.
+        language: String,
+    },
+}
+
+impl PistonError {
+    /// Whether or not this error was caused by a request timing out.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the request timed out.
+    ///
+    /// # Example
+    /// ```
+    /// let e = piston_rs::PistonError::RateLimited { retry_after: None };
+    ///
+    /// assert!(!e.is_timeout());
+    /// ```
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::Http(e) => e.is_timeout(),
+            Self::Timeout { .. } => true,
+            #[cfg(feature = "ws")]
+            Self::StreamTimeout { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// How long Piston asked us to wait before trying again.
+    ///
+    /// # Returns
+    /// - [`Option<Duration>`] - The delay from the `Retry-After`
+    ///   header, if this is a [`PistonError::RateLimited`] error and
+    ///   Piston sent one.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let e = piston_rs::PistonError::RateLimited {
+    ///     retry_after: Some(Duration::from_secs(2)),
+    /// };
+    ///
+    /// assert_eq!(e.retry_after(), Some(Duration::from_secs(2)));
+    /// ```
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Generates the error for a non 200 response from Piston.
+    ///
+    /// # Arguments
+    /// - `status` - The status code of the response.
+    /// - `headers` - The headers of the response.
+    /// - `body` - The body of the response.
+    ///
+    /// # Returns
+    /// - [`PistonError`] - The new error.
+    pub(crate) fn from_response(status: StatusCode, headers: &HeaderMap, body: String) -> Self {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited {
+                retry_after: parse_retry_after(headers),
+            },
+            _ => Self::Api {
+                status: status.as_u16(),
+                message: error_message(headers, body),
+            },
+        }
+    }
+
+    /// Converts an http error caused by a timeout into a
+    /// [`PistonError::Timeout`], with the endpoint and elapsed time.
+    /// Other errors are returned unchanged.
+    ///
+    /// # Arguments
+    /// - `endpoint` - The endpoint the request was sent to.
+    /// - `elapsed` - How long the request ran for.
+    ///
+    /// # Returns
+    /// - [`PistonError`] - The converted error.
+    pub(crate) fn with_timeout(self, endpoint: &str, elapsed: Duration) -> Self {
+        match &self {
+            Self::Http(e) if e.is_timeout() => Self::Timeout {
+                endpoint: endpoint.to_string(),
+                elapsed,
+            },
+            _ => self,
+        }
+    }
+
+    /// Converts an error response saying the runtime is unknown into a
+    /// [`PistonError::UnknownLanguage`]. Other errors are returned
+    /// unchanged.
+    ///
+    /// # Arguments
+    /// - `language` - The language that was requested.
+    ///
+    /// # Returns
+    /// - [`PistonError`] - The converted error.
+    pub(crate) fn with_language(self, language: &str) -> Self {
+        match self.bad_request_message() {
+            Some(message) if is_unknown_runtime(&message) => Self::UnknownLanguage {
+                language: language.to_string(),
+            },
+            _ => self,
+        }
+    }
+
+    /// Converts an error response saying the package is not installed
+    /// into a [`PistonError::PackageNotInstalled`]. Other errors are
+    /// returned unchanged.
+    ///
+    /// # Arguments
+    /// - `language` - The language of the package.
+    /// - `version` - The version of the package.
+    ///
+    /// # Returns
+    /// - [`PistonError`] - The converted error.
+    pub(crate) fn with_package(self, language: &str, version: &str) -> Self {
+        match self.bad_request_message() {
+            Some(message) if is_not_installed(&message) => Self::PackageNotInstalled {
+                language: language.to_string(),
+                version: version.to_string(),
+            },
+            _ => self,
+        }
+    }
+
+    /// The message of a 400 error response, if Piston sent one as
+    /// json.
+    ///
+    /// # Returns
+    /// - [`Option<String>`] - The message, if any.
+    fn bad_request_message(&self) -> Option<String> {
+        #[derive(serde::Deserialize)]
+        struct ErrorBody {
+            message: String,
+        }
+
+        match self {
+            Self::Api {
+                status: 400,
+                message,
+            } => serde_json::from_str::<ErrorBody>(message)
+                .ok()
+                .map(|body| body.message),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PistonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "{}", e),
+            Self::Timeout { endpoint, elapsed } => write!(
+                f,
+                "Request to {} timed out after {}ms",
+                endpoint,
+                elapsed.as_millis()
+            ),
+            #[cfg(feature = "ws")]
+            Self::StreamTimeout {
+                endpoint,
+                elapsed,
+                partial,
+            } => write!(
+                f,
+                "Streaming from {} timed out after {}ms, with {} chunks received",
+                endpoint,
+                elapsed.as_millis(),
+                partial.len()
+            ),
+            Self::RateLimited {
+                retry_after: Some(d),
+            } => write!(f, "Rate limited by Piston, retry after {}s", d.as_secs()),
+            Self::RateLimited { retry_after: None } => write!(f, "Rate limited by Piston"),
+            Self::Api { status, message } => write!(f, "{}: {}", status, message),
+            #[cfg(feature = "ws")]
+            Self::WebSocket(e) => write!(f, "{}", e),
+            Self::Deserialize(e) => write!(f, "Unable to deserialize response: {}", e),
+            Self::OutputTooLarge { limit } => {
+                write!(f, "Response from Piston exceeded {} bytes", limit)
+            }
+            Self::InvalidExecutor(reason) => write!(f, "Invalid executor: {}", reason),
+            Self::InvalidConfig(reason) => write!(f, "Invalid configuration: {}", reason),
+            Self::PackagesUnavailable { status } => write!(
+                f,
+                "{}: Package management is not available on this Piston instance",
+                status
+            ),
+            Self::PackageNotInstalled { language, version } => {
+                write!(f, "Package {} {} is not installed", language, version)
+            }
+            Self::UnknownLanguage { language } => write!(f, "Unknown language: {}", language),
+        }
+    }
+}
+
+impl std::error::Error for PistonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => Some(e),
+            #[cfg(feature = "ws")]
+            Self::WebSocket(e) => Some(e),
+            Self::Deserialize(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for PistonError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
+#[cfg(feature = "ws")]
+impl From<tokio_tungstenite::tungstenite::Error> for PistonError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for PistonError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Deserialize(e)
+    }
+}
+
+/// Generates the message for an error response. Bodies that are not
+/// JSON, like the html error pages of a reverse proxy, are collapsed
+/// onto one line and truncated.
+///
+/// # Arguments
+/// - `headers` - The headers of the response.
+/// - `body` - The body of the response.
+///
+/// # Returns
+/// - [`String`] - The message.
+fn error_message(headers: &HeaderMap, body: String) -> String {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or("")
+        .trim();
+
+    if content_type.is_empty() || content_type.contains("json") {
+        return body;
+    }
+
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match body.char_indices().nth(MAX_ERROR_BODY_LEN) {
+        Some((end, _)) => format!("Unexpected {} response: {}...", content_type, &body[..end]),
+        None => format!("Unexpected {} response: {}", content_type, body),
+    }
+}
+
+/// Whether or not the message Piston sent says the requested runtime
+/// is unknown, i.e. "python-0.0.0 runtime is unknown".
+///
+/// # Arguments
+/// - `message` - The message Piston sent.
+///
+/// # Returns
+/// - [`bool`] - [`true`] if the runtime is unknown.
+fn is_unknown_runtime(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("runtime is unknown") || message.contains("unknown language")
+}
+
+/// Whether or not the message Piston sent says the requested package
+/// is not installed, i.e. "python-3.10.0 not installed".
+///
+/// # Arguments
+/// - `message` - The message Piston sent.
+///
+/// # Returns
+/// - [`bool`] - [`true`] if the package is not installed.
+fn is_not_installed(message: &str) -> bool {
+    message.to_ascii_lowercase().contains("not installed")
+}
+
+/// Parses the `Retry-After` header, if present. Both the number of
+/// seconds and the http date forms are supported.
+///
+/// # Arguments
+/// - `headers` - The headers of the response.
+///
+/// # Returns
+/// - [`Option<Duration>`] - How long to wait, if Piston said.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get("Retry-After")?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    let delay = date.duration_since(SystemTime::now());
+
+    // A date in the past means we can retry right away.
+    Some(delay.unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod test_error_private {
+    use super::parse_retry_after;
+    use super::PistonError;
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_from_response_rate_limited() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Retry-After", HeaderValue::from_static("3"));
+        let err = PistonError::from_response(StatusCode::TOO_MANY_REQUESTS, &headers, "".into());
+
+        match err {
+            PistonError::RateLimited { retry_after } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(3)));
+            }
+            _ => panic!("Expected a rate limit error"),
+        }
+    }
+
+    #[test]
+    fn test_from_response_rate_limited_no_header() {
+        let headers = HeaderMap::new();
+        let err = PistonError::from_response(StatusCode::TOO_MANY_REQUESTS, &headers, "".into());
+
+        assert!(matches!(
+            err,
+            PistonError::RateLimited { retry_after: None }
+        ));
+        assert_eq!(format!("{}", err), "Rate limited by Piston");
+    }
+
+    #[test]
+    fn test_from_response_api() {
+        let headers = HeaderMap::new();
+        let err = PistonError::from_response(StatusCode::BAD_REQUEST, &headers, "Oops".into());
+
+        match &err {
+            PistonError::Api { status, message } => {
+                assert_eq!(*status, 400);
+                assert_eq!(message, "Oops");
+            }
+            _ => panic!("Expected an api error"),
+        }
+
+        assert_eq!(format!("{}", err), "400: Oops");
+        assert!(!err.is_timeout());
+    }
+
+    #[test]
+    fn test_from_response_html() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        let body = format!(
+            "<html>\n  <h1>502 Bad Gateway</h1>{}</html>",
+            "x".repeat(10_000)
+        );
+        let err = PistonError::from_response(StatusCode::BAD_GATEWAY, &headers, body);
+
+        match err {
+            PistonError::Api { status, message } => {
+                assert_eq!(status, 502);
+                assert!(message.starts_with("Unexpected text/html response: <html> <h1>502"));
+                assert!(message.ends_with("..."));
+                assert!(message.len() < 300);
+            }
+            _ => panic!("Expected an api error"),
+        }
+    }
+
+    #[test]
+    fn test_from_response_json_untouched() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+        let body = r#"{"message":"Oops"}"#.to_string();
+        let err = PistonError::from_response(StatusCode::BAD_REQUEST, &headers, body.clone());
+
+        assert!(matches!(err, PistonError::Api { message, .. } if message == body));
+    }
+
+    #[test]
+    fn test_with_language() {
+        let unknown = PistonError::Api {
+            status: 400,
+            message: r#"{"message":"pyhton-* runtime is unknown"}"#.to_string(),
+        };
+        let other = PistonError::Api {
+            status: 400,
+            message: r#"{"message":"files is required as an array"}"#.to_string(),
+        };
+
+        assert_eq!(
+            format!("{}", unknown.with_language("pyhton")),
+            "Unknown language: pyhton"
+        );
+        assert!(matches!(
+            other.with_language("python"),
+            PistonError::Api { status: 400, .. }
+        ));
+    }
+
+    #[test]
+    fn test_with_package() {
+        let not_installed = PistonError::Api {
+            status: 400,
+            message: r#"{"message":"python-3.10.0 not installed"}"#.to_string(),
+        };
+        let other = PistonError::Api {
+            status: 400,
+            message: r#"{"message":"language is required"}"#.to_string(),
+        };
+
+        assert!(matches!(
+            not_installed.with_package("python", "3.10.0"),
+            PistonError::PackageNotInstalled { .. }
+        ));
+        assert!(matches!(
+            other.with_package("python", "3.10.0"),
+            PistonError::Api { status: 400, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_retry_after_date() {
+        let date = SystemTime::now() + Duration::from_secs(120);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Retry-After",
+            HeaderValue::from_str(&httpdate::fmt_http_date(date)).unwrap(),
+        );
+
+        let delay = parse_retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(100));
+        assert!(delay <= Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_parse_retry_after_past_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Retry-After",
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_parse_retry_after_invalid() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Retry-After", HeaderValue::from_static("soon"));
+
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_retry_after_other_error() {
+        let err = PistonError::Api {
+            status: 500,
+            message: String::new(),
+        };
+
+        assert_eq!(err.retry_after(), None);
+    }
+}
@@ -1,6 +1,109 @@
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
 
 use super::File;
+use super::PistonError;
+
+/// A signal sent to a process during execution, as reported in
+/// [`ExecResult::signal`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// `SIGHUP` - The terminal hung up.
+    Hup,
+    /// `SIGINT` - The process was interrupted.
+    Int,
+    /// `SIGQUIT` - The process was asked to quit.
+    Quit,
+    /// `SIGILL` - The process executed an illegal instruction.
+    Ill,
+    /// `SIGTRAP` - The process hit a trap.
+    Trap,
+    /// `SIGABRT` - The process aborted, i.e. a panic or failed
+    /// assertion.
+    Abrt,
+    /// `SIGBUS` - The process accessed invalid memory.
+    Bus,
+    /// `SIGFPE` - An arithmetic error, like dividing by zero.
+    Fpe,
+    /// `SIGKILL` - The process was killed. Piston does this when the
+    /// process exceeds its memory limit or timeout.
+    Kill,
+    /// `SIGUSR1` - A user defined signal.
+    Usr1,
+    /// `SIGSEGV` - The process accessed memory it was not allowed to.
+    Segv,
+    /// `SIGUSR2` - A user defined signal.
+    Usr2,
+    /// `SIGPIPE` - The process wrote to a closed pipe.
+    Pipe,
+    /// `SIGALRM` - A timer set by the process expired.
+    Alrm,
+    /// `SIGTERM` - The process was asked to terminate.
+    Term,
+    /// `SIGXCPU` - The process exceeded its CPU time limit.
+    Xcpu,
+    /// `SIGXFSZ` - The process exceeded its file size limit.
+    Xfsz,
+    /// Any other signal, by name.
+    Other(String),
+}
+
+impl From<&str> for Signal {
+    /// Parses a signal from its name, i.e. "SIGKILL".
+    fn from(name: &str) -> Self {
+        match name {
+            "SIGHUP" => Self::Hup,
+            "SIGINT" => Self::Int,
+            "SIGQUIT" => Self::Quit,
+            "SIGILL" => Self::Ill,
+            "SIGTRAP" => Self::Trap,
+            "SIGABRT" => Self::Abrt,
+            "SIGBUS" => Self::Bus,
+            "SIGFPE" => Self::Fpe,
+            "SIGKILL" => Self::Kill,
+            "SIGUSR1" => Self::Usr1,
+            "SIGSEGV" => Self::Segv,
+            "SIGUSR2" => Self::Usr2,
+            "SIGPIPE" => Self::Pipe,
+            "SIGALRM" => Self::Alrm,
+            "SIGTERM" => Self::Term,
+            "SIGXCPU" => Self::Xcpu,
+            "SIGXFSZ" => Self::Xfsz,
+            _ => Self::Other(name.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Signal {
+    /// Displays the name of the signal, i.e. "SIGKILL".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Hup => "SIGHUP",
+            Self::Int => "SIGINT",
+            Self::Quit => "SIGQUIT",
+            Self::Ill => "SIGILL",
+            Self::Trap => "SIGTRAP",
+            Self::Abrt => "SIGABRT",
+            Self::Bus => "SIGBUS",
+            Self::Fpe => "SIGFPE",
+            Self::Kill => "SIGKILL",
+            Self::Usr1 => "SIGUSR1",
+            Self::Segv => "SIGSEGV",
+            Self::Usr2 => "SIGUSR2",
+            Self::Pipe => "SIGPIPE",
+            Self::Alrm => "SIGALRM",
+            Self::Term => "SIGTERM",
+            Self::Xcpu => "SIGXCPU",
+            Self::Xfsz => "SIGXFSZ",
+            Self::Other(name) => name,
+        };
+
+        write!(f, "{}", name)
+    }
+}
 
 /// The result of code execution returned by Piston.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,14 +118,32 @@ pub struct ExecResult {
     pub code: isize,
     /// The optional signal sent to the process. (`SIGKILL` etc)
     pub signal: Option<String>,
+    /// Why the step failed, if it did and Piston said. i.e. "TO" if it
+    /// timed out, or "SG" if it was killed by a signal. Only sent by
+    /// newer versions of Piston. Sent as `status`, not to be confused
+    /// with the http status of [`ExecResponse::status`].
+    #[serde(default, rename = "status", skip_serializing_if = "Option::is_none")]
+    pub stage_status: Option<String>,
+    /// A description of why the step failed, if Piston sent one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// How long the step ran for, in milliseconds, if Piston said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_time: Option<u64>,
 }
 
 impl ExecResult {
+    /// The default maximum size of each of `stdout` and `stderr`, in
+    /// bytes, that Piston keeps before truncating the rest. This is
+    /// Piston's `output_max_size` setting, which self-hosted instances
+    /// may change.
+    pub const DEFAULT_OUTPUT_LIMIT: usize = 1024;
+
     /// Whether or not the execution was ok.
     ///
     /// # Returns
     /// - [`bool`] - [`true`] if the execution returned a zero exit
-    /// code.
+    ///   code.
     pub fn is_ok(&self) -> bool {
         self.code == 0
     }
@@ -31,10 +152,195 @@ impl ExecResult {
     ///
     /// # Returns
     /// - [`bool`] - [`true`] if the execution returned a non zero exit
-    /// code.
+    ///   code.
     pub fn is_err(&self) -> bool {
         self.code != 0
     }
+
+    /// The lines of the combined output. Piston interleaves `stdout`
+    /// and `stderr` in [`ExecResult::output`] in the order they were
+    /// written.
+    ///
+    /// # Returns
+    /// - [`impl Iterator<Item = &str>`] - The lines of output.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "1\n3\n".to_string(),
+    ///     stderr: "2\n".to_string(),
+    ///     output: "1\n2\n3\n".to_string(),
+    ///     code: 0,
+    ///     signal: None,
+    ///     stage_status: None,
+    ///     message: None,
+    ///     wall_time: None,
+    /// };
+    ///
+    /// assert_eq!(result.lines().collect::<Vec<_>>(), vec!["1", "2", "3"]);
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.output.lines()
+    }
+
+    /// The signal sent to the process, if any, as a [`Signal`].
+    ///
+    /// # Returns
+    /// - [`Option<Signal>`] - The signal, if any.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: String::new(),
+    ///     stderr: String::new(),
+    ///     output: String::new(),
+    ///     code: 137,
+    ///     signal: Some("SIGKILL".to_string()),
+    ///     stage_status: None,
+    ///     message: None,
+    ///     wall_time: None,
+    /// };
+    ///
+    /// assert_eq!(result.signal_kind(), Some(piston_rs::Signal::Kill));
+    /// ```
+    pub fn signal_kind(&self) -> Option<Signal> {
+        self.signal.as_deref().map(Signal::from)
+    }
+
+    /// The text sent to `stdout`, with leading and trailing whitespace,
+    /// like the newline from `println!`, removed.
+    ///
+    /// # Returns
+    /// - [`&str`] - The trimmed `stdout`.
+    pub fn stdout_trimmed(&self) -> &str {
+        self.stdout.trim()
+    }
+
+    /// Whether or not Piston may have truncated the output. Piston
+    /// doesn't say when it truncates output, so this is a heuristic:
+    /// it is [`true`] when `stdout` or `stderr` is at least `limit`
+    /// bytes long.
+    ///
+    /// # Arguments
+    /// - `limit` - The output limit of the Piston instance, usually
+    ///   [`ExecResult::DEFAULT_OUTPUT_LIMIT`].
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the output may have been cut off.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "y\n".repeat(512),
+    ///     stderr: String::new(),
+    ///     output: "y\n".repeat(512),
+    ///     code: 0,
+    ///     signal: None,
+    ///     stage_status: None,
+    ///     message: None,
+    ///     wall_time: None,
+    /// };
+    ///
+    /// assert!(result.possibly_truncated(piston_rs::ExecResult::DEFAULT_OUTPUT_LIMIT));
+    /// ```
+    pub fn possibly_truncated(&self, limit: usize) -> bool {
+        self.stdout.len() >= limit || self.stderr.len() >= limit
+    }
+
+    /// Whether or not the execution produced no output.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if both `stdout` and `stderr` are empty.
+    pub fn is_empty(&self) -> bool {
+        self.stdout.is_empty() && self.stderr.is_empty()
+    }
+
+    /// Summarizes the exit code and signal of the execution.
+    ///
+    /// # Returns
+    /// - [`String`] - The summary.
+    fn summary(&self) -> String {
+        match &self.signal {
+            Some(signal) => format!("Exited with code {} ({})", self.code, signal),
+            None => format!("Exited with code {}", self.code),
+        }
+    }
+
+    /// Renders the result for display in a terminal, with `stderr` in
+    /// red and a summary of the exit code and signal. Colors are left
+    /// out if the `NO_COLOR` environment variable is set.
+    ///
+    /// Requires the `color` feature to be enabled.
+    ///
+    /// # Returns
+    /// - [`String`] - The rendered result.
+    ///
+    /// # Example
+    /// ```
+    /// let result = piston_rs::ExecResult {
+    ///     stdout: "42\n".to_string(),
+    ///     stderr: String::new(),
+    ///     output: "42\n".to_string(),
+    ///     code: 0,
+    ///     signal: None,
+    ///     stage_status: None,
+    ///     message: None,
+    ///     wall_time: None,
+    /// };
+    ///
+    /// assert!(result.to_colored_string().contains("42"));
+    /// ```
+    #[cfg(feature = "color")]
+    pub fn to_colored_string(&self) -> String {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        self.render(!no_color)
+    }
+
+    /// Renders the result, optionally with colors.
+    ///
+    /// # Arguments
+    /// - `color` - Whether or not to use colors.
+    ///
+    /// # Returns
+    /// - [`String`] - The rendered result.
+    #[cfg(feature = "color")]
+    fn render(&self, color: bool) -> String {
+        use owo_colors::OwoColorize;
+
+        let mut rendered = String::new();
+
+        for (text, is_stderr) in [(&self.stdout, false), (&self.stderr, true)] {
+            if text.is_empty() {
+                continue;
+            }
+
+            match color && is_stderr {
+                true => rendered.push_str(&text.red().to_string()),
+                false => rendered.push_str(text),
+            }
+
+            if !text.ends_with('\n') {
+                rendered.push('\n');
+            }
+        }
+
+        let summary = self.summary();
+
+        match (color, self.is_ok()) {
+            (true, true) => rendered.push_str(&summary.green().to_string()),
+            (true, false) => rendered.push_str(&summary.red().to_string()),
+            (false, _) => rendered.push_str(&summary),
+        }
+
+        rendered
+    }
+}
+
+impl std::fmt::Display for ExecResult {
+    /// Displays the combined output of the execution.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.output)
+    }
 }
 
 /// Raw response received from Piston
@@ -52,6 +358,270 @@ pub struct RawExecResponse {
     pub compile: Option<ExecResult>,
 }
 
+/// How an execution went, as returned by [`ExecResponse::outcome`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The code compiled (if needed) and ran with a zero exit code.
+    Success,
+    /// The code failed to compile.
+    CompileError,
+    /// The code compiled, but running it failed.
+    RuntimeError {
+        /// The exit code returned by the process.
+        code: isize,
+        /// The signal sent to the process, if any.
+        signal: Option<String>,
+    },
+    /// Piston responded with a non 200 status code.
+    ApiError {
+        /// The status code returned by Piston.
+        status: u16,
+    },
+}
+
+/// Why an execution failed, as returned by
+/// [`ExecResponse::into_result`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecFailure {
+    /// The code failed to compile.
+    Compile {
+        /// The exit code returned by the compiler.
+        code: isize,
+        /// The text the compiler sent to `stderr`.
+        stderr: String,
+    },
+    /// The code compiled, but running it failed.
+    Run {
+        /// The exit code returned by the process.
+        code: isize,
+        /// The signal sent to the process, if any.
+        signal: Option<String>,
+        /// The text the process sent to `stderr`.
+        stderr: String,
+    },
+    /// Piston responded with a non 200 status code.
+    Api {
+        /// The status code returned by Piston.
+        status: u16,
+    },
+}
+
+impl std::fmt::Display for ExecFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compile { code, stderr } => {
+                write!(f, "Compilation failed with code {}", code)?;
+                write_stderr(f, stderr)
+            }
+            Self::Run {
+                code,
+                signal: Some(signal),
+                stderr,
+            } => {
+                write!(f, "Run failed with code {} ({})", code, signal)?;
+                write_stderr(f, stderr)
+            }
+            Self::Run {
+                code,
+                signal: None,
+                stderr,
+            } => {
+                write!(f, "Run failed with code {}", code)?;
+                write_stderr(f, stderr)
+            }
+            Self::Api { status } => write!(f, "Piston responded with status {}", status),
+        }
+    }
+}
+
+impl std::error::Error for ExecFailure {}
+
+/// Writes the trimmed `stderr` of a failure, if there is any.
+///
+/// # Arguments
+/// - `f` - The formatter to write to.
+/// - `stderr` - The text sent to `stderr`.
+///
+/// # Returns
+/// - [`std::fmt::Result`] - The result of writing.
+fn write_stderr(f: &mut std::fmt::Formatter<'_>, stderr: &str) -> std::fmt::Result {
+    match stderr.trim() {
+        "" => Ok(()),
+        stderr => write!(f, ": {}", stderr),
+    }
+}
+
+/// How the output of two executions differs, as returned by
+/// [`ExecResponse::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputDiff {
+    /// Whether or not both runs sent the same text to `stdout`.
+    pub stdout_matches: bool,
+    /// Whether or not both runs sent the same text to `stderr`.
+    pub stderr_matches: bool,
+    /// Whether or not both executions have the same
+    /// [`ExecResponse::exit_code`].
+    pub exit_matches: bool,
+    /// The line by line differences between the `stdout` of both
+    /// runs, including the lines that are unchanged.
+    pub stdout_diff: Vec<DiffLine>,
+}
+
+impl OutputDiff {
+    /// Whether or not `stdout`, `stderr` and the exit code all match.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the outputs are the same.
+    pub fn is_match(&self) -> bool {
+        self.stdout_matches && self.stderr_matches && self.exit_matches
+    }
+}
+
+/// A single line of an [`OutputDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    /// The line is in both outputs.
+    Unchanged(String),
+    /// The line is only in the output of the response `diff` was
+    /// called on.
+    Removed(String),
+    /// The line is only in the output of the other response.
+    Added(String),
+}
+
+/// The failure returned by [`ExecResponse::assert_stdout`] when the
+/// output is not what was expected.
+///
+/// Displaying it shows a line by line diff, with `-` before the
+/// expected lines that are missing and `+` before the unexpected lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssertionFailure {
+    /// The expected output, trimmed.
+    pub expected: String,
+    /// The actual output, trimmed.
+    pub actual: String,
+}
+
+impl std::fmt::Display for AssertionFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "stdout did not match the expected output")?;
+
+        for line in diff_lines(&self.expected, &self.actual) {
+            match line {
+                DiffLine::Unchanged(line) => write!(f, "\n  {}", line)?,
+                DiffLine::Removed(line) => write!(f, "\n- {}", line)?,
+                DiffLine::Added(line) => write!(f, "\n+ {}", line)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for AssertionFailure {}
+
+/// The largest table [`diff_lines`] builds, in entries. Larger diffs
+/// fall back to removing every differing line, then adding the new
+/// ones.
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+/// Diffs two texts line by line, using their longest common
+/// subsequence of lines.
+///
+/// The lines the texts start and end with are unchanged. If what is
+/// left between them is too large to diff in a bounded amount of
+/// memory, its old lines are all removed and its new lines all added.
+///
+/// # Arguments
+/// - `old` - The original text.
+/// - `new` - The text to compare it to.
+///
+/// # Returns
+/// - [`Vec<DiffLine>`] - The lines of both texts, in order.
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    lines.extend(
+        old[..prefix]
+            .iter()
+            .map(|l| DiffLine::Unchanged(l.to_string())),
+    );
+    lines.extend(diff_middle(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|l| DiffLine::Unchanged(l.to_string())),
+    );
+    lines
+}
+
+/// Diffs the lines between the common start and end of two texts.
+///
+/// # Arguments
+/// - `old` - The original lines.
+/// - `new` - The lines to compare them to.
+///
+/// # Returns
+/// - [`Vec<DiffLine>`] - The lines of both, in order.
+fn diff_middle(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let cells = (old.len() + 1).saturating_mul(new.len() + 1);
+
+    if cells > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|l| DiffLine::Removed(l.to_string()))
+            .chain(new.iter().map(|l| DiffLine::Added(l.to_string())))
+            .collect();
+    }
+
+    // lengths[i][j] is the length of the longest common subsequence
+    // of old[i..] and new[j..].
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+
+    lines.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    lines.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    lines
+}
+
 /// A response returned by Piston when executing code.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExecResponse {
@@ -66,23 +636,393 @@ pub struct ExecResponse {
     pub compile: Option<ExecResult>,
     /// The response status returned by Piston.
     pub status: u16,
+    /// How long the request to Piston took, measured by the client.
+    /// This includes the network round trip, so it is longer than the
+    /// time the code spent running. [`None`] if the response was not
+    /// received from Piston, i.e. it was deserialized from disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed: Option<Duration>,
 }
 
 impl ExecResponse {
-    /// Whether or not the request to Piston succeeded.
+    /// Whether or not the request to Piston succeeded, and the code
+    /// compiled and ran without errors.
     ///
     /// # Returns
-    /// - [`bool`] - [`true`] if a 200 status code was received from Piston.
+    /// - [`bool`] - [`true`] if a 200 status code was received from
+    ///   Piston, and both the compile step (if any) and the run step
+    ///   returned a zero exit code.
     pub fn is_ok(&self) -> bool {
-        self.status == 200
+        let compiled = self.compile.as_ref().is_none_or(|c| c.is_ok());
+        self.status == 200 && compiled && self.run.is_ok()
     }
 
-    /// Whether or not the request to Piston failed.
+    /// Whether or not the request to Piston failed, or the code failed
+    /// to compile or run.
     ///
     /// # Returns
-    /// - [`bool`] - [`true`] if a non 200 status code was received from Piston.
+    /// - [`bool`] - [`true`] if a non 200 status code was received
+    ///   from Piston, or either the compile step or the run step
+    ///   returned a non zero exit code.
     pub fn is_err(&self) -> bool {
-        self.status != 200
+        !self.is_ok()
+    }
+
+    /// Compares the output of this execution to another, i.e. a
+    /// reference run. The language and version are ignored.
+    ///
+    /// # Arguments
+    /// - `other` - The response to compare to.
+    ///
+    /// # Returns
+    /// - [`OutputDiff`] - Which parts of the output match, and the line
+    ///   by line differences in `stdout`.
+    ///
+    /// # Example
+    /// ```
+    /// use piston_rs::DiffLine;
+    ///
+    /// # let response = |stdout: &str| piston_rs::ExecResponse {
+    /// #     language: "python".to_string(),
+    /// #     version: "3.10.0".to_string(),
+    /// #     run: piston_rs::ExecResult {
+    /// #         stdout: stdout.to_string(),
+    /// #         stderr: String::new(),
+    /// #         output: stdout.to_string(),
+    /// #         code: 0,
+    /// #         signal: None,
+    /// #         stage_status: None,
+    /// #         message: None,
+    /// #         wall_time: None,
+    /// #     },
+    /// #     compile: None,
+    /// #     status: 200,
+    /// #     elapsed: None,
+    /// # };
+    /// let expected = response("1\n2\n");
+    /// let actual = response("1\n3\n");
+    /// let diff = expected.diff(&actual);
+    ///
+    /// assert!(!diff.stdout_matches);
+    /// assert!(diff.exit_matches);
+    /// assert_eq!(
+    ///     diff.stdout_diff,
+    ///     vec![
+    ///         DiffLine::Unchanged("1".to_string()),
+    ///         DiffLine::Removed("2".to_string()),
+    ///         DiffLine::Added("3".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn diff(&self, other: &ExecResponse) -> OutputDiff {
+        OutputDiff {
+            stdout_matches: self.run.stdout == other.run.stdout,
+            stderr_matches: self.run.stderr == other.run.stderr,
+            exit_matches: self.exit_code() == other.exit_code(),
+            stdout_diff: diff_lines(&self.run.stdout, &other.run.stdout),
+        }
+    }
+
+    /// Checks that the run step sent the expected text to `stdout`.
+    /// Both are trimmed before they are compared.
+    ///
+    /// # Arguments
+    /// - `expected` - The expected output.
+    ///
+    /// # Returns
+    /// - [`Result<(), AssertionFailure>`] - Nothing, or the expected
+    ///   and actual output if they differ.
+    ///
+    /// # Example
+    /// ```
+    /// # let response = piston_rs::ExecResponse {
+    /// #     language: "python".to_string(),
+    /// #     version: "3.10.0".to_string(),
+    /// #     run: piston_rs::ExecResult {
+    /// #         stdout: "1\n3\n".to_string(),
+    /// #         stderr: String::new(),
+    /// #         output: "1\n3\n".to_string(),
+    /// #         code: 0,
+    /// #         signal: None,
+    /// #         stage_status: None,
+    /// #         message: None,
+    /// #         wall_time: None,
+    /// #     },
+    /// #     compile: None,
+    /// #     status: 200,
+    /// #     elapsed: None,
+    /// # };
+    /// let failure = response.assert_stdout("1\n2").unwrap_err();
+    ///
+    /// assert_eq!(failure.actual, "1\n3");
+    /// assert_eq!(
+    ///     failure.to_string(),
+    ///     "stdout did not match the expected output\n  1\n- 2\n+ 3"
+    /// );
+    /// ```
+    pub fn assert_stdout(&self, expected: &str) -> Result<(), AssertionFailure> {
+        let expected = expected.trim();
+
+        match self.stdout() == expected {
+            true => Ok(()),
+            false => Err(AssertionFailure {
+                expected: expected.to_string(),
+                actual: self.stdout().to_string(),
+            }),
+        }
+    }
+
+    /// The response status returned by Piston, as a
+    /// [`reqwest::StatusCode`].
+    ///
+    /// # Returns
+    /// - [`reqwest::StatusCode`] - The status code. Values that are not
+    ///   valid status codes are reported as `500 Internal Server Error`.
+    ///
+    /// # Example
+    /// ```
+    /// # let response = piston_rs::ExecResponse {
+    /// #     language: "python".to_string(),
+    /// #     version: "3.10.0".to_string(),
+    /// #     run: piston_rs::ExecResult {
+    /// #         stdout: String::new(),
+    /// #         stderr: String::new(),
+    /// #         output: String::new(),
+    /// #         code: 0,
+    /// #         signal: None,
+    /// #         stage_status: None,
+    /// #         message: None,
+    /// #         wall_time: None,
+    /// #     },
+    /// #     compile: None,
+    /// #     status: 200,
+    /// #     elapsed: None,
+    /// # };
+    /// assert!(response.status_code().is_success());
+    /// ```
+    pub fn status_code(&self) -> reqwest::StatusCode {
+        reqwest::StatusCode::from_u16(self.status)
+            .unwrap_or(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Whether or not Piston compiled the code. Interpreted languages,
+    /// like Python, have no compile step, so [`ExecResponse::compile`]
+    /// is [`None`] for them.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if there was a compile step.
+    pub fn was_compiled(&self) -> bool {
+        self.compile.is_some()
+    }
+
+    /// The text the run step sent to `stdout`, trimmed. See
+    /// [`ExecResult::stdout_trimmed`].
+    ///
+    /// # Returns
+    /// - [`&str`] - The trimmed `stdout` of the run step.
+    pub fn stdout(&self) -> &str {
+        self.run.stdout_trimmed()
+    }
+
+    /// The combined output of the compile step, i.e. the compiler's
+    /// diagnostics.
+    ///
+    /// # Returns
+    /// - [`Option<&str>`] - The output of the compile step, or [`None`]
+    ///   if there was no compile step.
+    pub fn compile_output(&self) -> Option<&str> {
+        self.compile.as_ref().map(|c| c.output.as_str())
+    }
+
+    /// The combined output of the run step.
+    ///
+    /// # Returns
+    /// - [`&str`] - The output of the run step.
+    pub fn run_output(&self) -> &str {
+        &self.run.output
+    }
+
+    /// Whether or not the compile step succeeded.
+    ///
+    /// # Returns
+    /// - [`Option<bool>`] - [`None`] if there was no compile step,
+    ///   otherwise [`true`] if it returned a zero exit code.
+    pub fn compile_succeeded(&self) -> Option<bool> {
+        self.compile.as_ref().map(|c| c.is_ok())
+    }
+
+    /// A single exit code for the whole execution, suitable for
+    /// passing on as the exit status of a process.
+    ///
+    /// The compile step takes precedence, so a failed compile masks
+    /// the code of the run step: this is the compile step's exit code
+    /// if it is non zero, otherwise the run step's exit code. Exit
+    /// codes too large for an [`i32`] are reported as `1`. The status
+    /// code from Piston is not considered.
+    ///
+    /// # Returns
+    /// - [`i32`] - The exit code, `0` if both steps succeeded.
+    ///
+    /// # Example
+    /// ```
+    /// # let response = piston_rs::ExecResponse {
+    /// #     language: "python".to_string(),
+    /// #     version: "3.10.0".to_string(),
+    /// #     run: piston_rs::ExecResult {
+    /// #         stdout: String::new(),
+    /// #         stderr: "Traceback\n".to_string(),
+    /// #         output: "Traceback\n".to_string(),
+    /// #         code: 1,
+    /// #         signal: None,
+    /// #         stage_status: None,
+    /// #         message: None,
+    /// #         wall_time: None,
+    /// #     },
+    /// #     compile: None,
+    /// #     status: 200,
+    /// #     elapsed: None,
+    /// # };
+    /// assert_eq!(response.exit_code(), 1);
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        let code = match &self.compile {
+            Some(compile) if compile.code != 0 => compile.code,
+            _ => self.run.code,
+        };
+
+        i32::try_from(code).unwrap_or(1)
+    }
+
+    /// Converts the response into a [`Result`], so failed executions
+    /// can be propagated with `?`.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, ExecFailure>`] - The response, if it
+    ///   [`ExecResponse::is_ok`], otherwise why it failed.
+    ///
+    /// # Example
+    /// ```
+    /// # let response = piston_rs::ExecResponse {
+    /// #     language: "python".to_string(),
+    /// #     version: "3.10.0".to_string(),
+    /// #     run: piston_rs::ExecResult {
+    /// #         stdout: "42\n".to_string(),
+    /// #         stderr: String::new(),
+    /// #         output: "42\n".to_string(),
+    /// #         code: 0,
+    /// #         signal: None,
+    /// #         stage_status: None,
+    /// #         message: None,
+    /// #         wall_time: None,
+    /// #     },
+    /// #     compile: None,
+    /// #     status: 200,
+    /// #     elapsed: None,
+    /// # };
+    /// match response.into_result() {
+    ///     Ok(response) => assert_eq!(response.run.stdout, "42\n".to_string()),
+    ///     Err(failure) => println!("{}", failure),
+    /// }
+    /// ```
+    pub fn into_result(self) -> Result<Self, ExecFailure> {
+        match self.outcome() {
+            Outcome::Success => Ok(self),
+            Outcome::ApiError { status } => Err(ExecFailure::Api { status }),
+            Outcome::CompileError => {
+                let compile = self.compile.unwrap_or(self.run);
+
+                Err(ExecFailure::Compile {
+                    code: compile.code,
+                    stderr: compile.stderr,
+                })
+            }
+            Outcome::RuntimeError { code, signal } => Err(ExecFailure::Run {
+                code,
+                signal,
+                stderr: self.run.stderr,
+            }),
+        }
+    }
+
+    /// How the execution went, as a single value to match on.
+    ///
+    /// The status code is checked first, then the compile step (if
+    /// any), and then the run step.
+    ///
+    /// # Returns
+    /// - [`Outcome`] - The outcome of the execution.
+    ///
+    /// # Example
+    /// ```
+    /// use piston_rs::Outcome;
+    ///
+    /// # let response = piston_rs::ExecResponse {
+    /// #     language: "python".to_string(),
+    /// #     version: "3.10.0".to_string(),
+    /// #     run: piston_rs::ExecResult {
+    /// #         stdout: String::new(),
+    /// #         stderr: String::new(),
+    /// #         output: String::new(),
+    /// #         code: 137,
+    /// #         signal: Some("SIGKILL".to_string()),
+    /// #         stage_status: None,
+    /// #         message: None,
+    /// #         wall_time: None,
+    /// #     },
+    /// #     compile: None,
+    /// #     status: 200,
+    /// #     elapsed: None,
+    /// # };
+    /// match response.outcome() {
+    ///     Outcome::Success => println!("Ok"),
+    ///     Outcome::CompileError => println!("Failed to compile"),
+    ///     Outcome::RuntimeError { code, .. } => println!("Exited with code {}", code),
+    ///     Outcome::ApiError { status } => println!("Piston responded with {}", status),
+    /// }
+    /// ```
+    pub fn outcome(&self) -> Outcome {
+        if self.status != 200 {
+            return Outcome::ApiError {
+                status: self.status,
+            };
+        }
+
+        if self.compile.as_ref().is_some_and(|c| c.is_err()) {
+            return Outcome::CompileError;
+        }
+
+        match self.run.is_ok() {
+            true => Outcome::Success,
+            false => Outcome::RuntimeError {
+                code: self.run.code,
+                signal: self.run.signal.clone(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ExecResponse {
+    /// Displays the language and version, the compile output (if
+    /// any), the run output, and the exit code.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.language, self.version)?;
+
+        for result in self.compile.iter().chain([&self.run]) {
+            if !result.output.is_empty() {
+                write!(f, "{}", result.output)?;
+
+                if !result.output.ends_with('\n') {
+                    writeln!(f)?;
+                }
+            }
+        }
+
+        let last = match &self.compile {
+            Some(c) if c.is_err() => c,
+            _ => &self.run,
+        };
+
+        write!(f, "{}", last.summary())
     }
 }
 
@@ -90,7 +1030,15 @@ impl ExecResponse {
 ///
 /// A convenient builder flow is provided by the methods associated with
 /// the `Executor`. These consume self and return self for chained calls.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// Executors compare equal, and hash the same, when every field is
+/// equal, so they can be used as [`std::collections::HashMap`] keys.
+///
+/// Executors use the same field names as Piston's api, so they can be
+/// loaded from config files with serde. Missing fields take their
+/// default values.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Executor {
     /// **Required** - The language to use for execution. Defaults to a
     /// new `String`.
@@ -172,6 +1120,293 @@ impl Executor {
         }
     }
 
+    /// Creates a new executor for a single file.
+    ///
+    /// This is a shortcut for chaining [`Executor::set_language`],
+    /// [`Executor::set_version`] and [`Executor::add_file`].
+    ///
+    /// # Arguments
+    /// - `language` - The language to use.
+    /// - `version` - The version of the language to use.
+    /// - `file` - The file to execute.
+    ///
+    /// # Returns
+    /// - [`Executor`] - The new Executor.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::default().set_content("print(42)");
+    /// let executor = piston_rs::Executor::from_file("python", "*", file.clone());
+    ///
+    /// assert_eq!(executor.language, "python".to_string());
+    /// assert_eq!(executor.files, vec![file]);
+    /// ```
+    pub fn from_file(language: &str, version: &str, file: File) -> Self {
+        Self::new()
+            .set_language(language)
+            .set_version(version)
+            .add_file(file)
+    }
+
+    /// Creates a new executor for a single unnamed file with the given
+    /// content.
+    ///
+    /// # Arguments
+    /// - `language` - The language to use.
+    /// - `version` - The version of the language to use.
+    /// - `content` - The code to execute.
+    ///
+    /// # Returns
+    /// - [`Executor`] - The new Executor.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::from_content("python", "3.10.0", "print(42)");
+    ///
+    /// assert_eq!(executor.version, "3.10.0".to_string());
+    /// assert_eq!(executor.files[0].content, "print(42)".to_string());
+    /// ```
+    pub fn from_content(language: &str, version: &str, content: &str) -> Self {
+        Self::from_file(language, version, File::default().set_content(content))
+    }
+
+    /// Creates a new executor for Python. A thin wrapper around
+    /// setting the language to "python" and the version.
+    ///
+    /// # Arguments
+    /// - `version` - The version of Python to use, or "*" for the
+    ///   latest.
+    ///
+    /// # Returns
+    /// - [`Executor`] - The new Executor.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::python("3.10.0");
+    ///
+    /// assert_eq!(executor.language, "python".to_string());
+    /// assert_eq!(executor.version, "3.10.0".to_string());
+    /// ```
+    pub fn python(version: &str) -> Self {
+        Self::new().set_language("python").set_version(version)
+    }
+
+    /// Creates a new executor for Rust. A thin wrapper around
+    /// setting the language to "rust" and the version.
+    ///
+    /// # Arguments
+    /// - `version` - The version of Rust to use, or "*" for the
+    ///   latest.
+    ///
+    /// # Returns
+    /// - [`Executor`] - The new Executor.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::rust("1.68.2");
+    ///
+    /// assert_eq!(executor.language, "rust".to_string());
+    /// assert_eq!(executor.version, "1.68.2".to_string());
+    /// ```
+    pub fn rust(version: &str) -> Self {
+        Self::new().set_language("rust").set_version(version)
+    }
+
+    /// Creates a new executor for JavaScript. A thin wrapper around
+    /// setting the language to "javascript" and the version.
+    ///
+    /// # Arguments
+    /// - `version` - The version of JavaScript to use, or "*" for the
+    ///   latest.
+    ///
+    /// # Returns
+    /// - [`Executor`] - The new Executor.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::javascript("18.15.0");
+    ///
+    /// assert_eq!(executor.language, "javascript".to_string());
+    /// assert_eq!(executor.version, "18.15.0".to_string());
+    /// ```
+    pub fn javascript(version: &str) -> Self {
+        Self::new().set_language("javascript").set_version(version)
+    }
+
+    /// Creates a new executor with every file in a directory, sorted
+    /// by name. The first file becomes the main file. Subdirectories
+    /// are skipped.
+    ///
+    /// # Arguments
+    /// - `language` - The language to use.
+    /// - `version` - The version of the language to use.
+    /// - `path` - The path to the directory.
+    ///
+    /// # Returns
+    /// - [`std::io::Result<Executor>`] - The new Executor, or the error
+    ///   if the directory or any of its files could not be read.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::from_dir("rust", "*", "src").unwrap();
+    ///
+    /// assert!(executor.files.iter().any(|f| f.name == "lib.rs"));
+    /// assert!(executor.files.windows(2).all(|w| w[0].name < w[1].name));
+    /// ```
+    pub fn from_dir(language: &str, version: &str, path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut paths = vec![];
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+
+            if entry.file_type()?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+
+        paths.sort();
+
+        let files = paths
+            .iter()
+            .map(File::from_path)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self::new()
+            .set_language(language)
+            .set_version(version)
+            .add_files(files))
+    }
+
+    /// Creates a new executor from JSON that uses the same field names
+    /// as Piston's api. Missing fields take their default values.
+    ///
+    /// # Arguments
+    /// - `json` - The JSON to parse.
+    ///
+    /// # Returns
+    /// - [`Result<Executor, serde_json::Error>`] - The new Executor, or
+    ///   the error if the JSON is invalid.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::from_json_str(r#"{
+    ///     "language": "python",
+    ///     "files": [{"content": "print(42)"}],
+    ///     "args": ["--verbose"]
+    /// }"#).unwrap();
+    ///
+    /// assert_eq!(executor.version, "*".to_string());
+    /// assert_eq!(executor.files[0].encoding, "utf8".to_string());
+    /// assert_eq!(executor.run_timeout, 3000);
+    /// ```
+    pub fn from_json_str(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Serializes the executor into the JSON body sent to Piston when
+    /// executing it.
+    ///
+    /// # Returns
+    /// - [`Result<String, serde_json::Error>`] - The JSON, or the error
+    ///   if the executor could not be serialized.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new().set_language("python");
+    /// let json = executor.to_json().unwrap();
+    ///
+    /// assert!(json.contains(r#""language":"python""#));
+    /// assert_eq!(piston_rs::Executor::from_json_str(&json).unwrap(), executor);
+    /// ```
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Saves the executor to disk as JSON, including the content of
+    /// its files. It can be loaded again with
+    /// [`Executor::load_from_path`].
+    ///
+    /// # Arguments
+    /// - `path` - The path to save the executor to.
+    ///
+    /// # Returns
+    /// - [`std::io::Result<()>`] - Nothing, or the error if the file
+    ///   could not be written.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let executor = piston_rs::Executor::from_content("python", "*", "print(42)");
+    ///
+    /// executor.save_to_path("submission.json").unwrap();
+    /// ```
+    pub fn save_to_path(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Loads an executor that was saved to disk with
+    /// [`Executor::save_to_path`].
+    ///
+    /// # Arguments
+    /// - `path` - The path to load the executor from.
+    ///
+    /// # Returns
+    /// - [`std::io::Result<Executor>`] - The loaded Executor, or the
+    ///   error if the file could not be read or is not a valid
+    ///   executor.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let executor = piston_rs::Executor::load_from_path("submission.json").unwrap();
+    ///
+    /// assert!(executor.validate().is_ok());
+    /// ```
+    pub fn load_from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Checks that the executor has everything Piston requires: a
+    /// language, a version, and at least one file.
+    ///
+    /// [`crate::Client::execute`] calls this before sending anything
+    /// to Piston.
+    ///
+    /// # Returns
+    /// - [`Result<(), PistonError>`] - Nothing, or a
+    ///   [`PistonError::InvalidExecutor`] describing the problem.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust");
+    ///
+    /// assert!(executor.validate().is_err());
+    ///
+    /// let executor = executor.add_file(piston_rs::File::default());
+    ///
+    /// assert!(executor.validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<(), PistonError> {
+        if self.language.is_empty() {
+            return Err(PistonError::InvalidExecutor(
+                "No language was set".to_string(),
+            ));
+        }
+
+        if self.version.is_empty() {
+            return Err(PistonError::InvalidExecutor(
+                "No version was set".to_string(),
+            ));
+        }
+
+        if self.files.is_empty() {
+            return Err(PistonError::InvalidExecutor(
+                "No files were added".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// Resets the executor back to a `new` state, ready to be
     /// configured again and sent to Piston after metadata is added.
     /// This method mutates the existing executor in place.
@@ -199,6 +1434,53 @@ impl Executor {
         self.run_memory_limit = -1;
     }
 
+    /// The language to use for execution.
+    ///
+    /// # Returns
+    /// - [`&str`] - The language.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("rust");
+    ///
+    /// assert_eq!(executor.get_language(), "rust");
+    /// ```
+    pub fn get_language(&self) -> &str {
+        &self.language
+    }
+
+    /// The version of the language to use for execution.
+    ///
+    /// # Returns
+    /// - [`&str`] - The version.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new();
+    ///
+    /// assert_eq!(executor.get_version(), "*");
+    /// ```
+    pub fn get_version(&self) -> &str {
+        &self.version
+    }
+
+    /// The files to send to Piston. The first file is the main file.
+    ///
+    /// # Returns
+    /// - [`&[File]`] - The files.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"));
+    ///
+    /// assert_eq!(executor.get_files()[0].name, "main.rs".to_string());
+    /// ```
+    pub fn get_files(&self) -> &[File] {
+        &self.files
+    }
+
     /// Sets the language to use for execution.
     ///
     /// # Arguments
@@ -220,7 +1502,9 @@ impl Executor {
         self
     }
 
-    /// Sets the version of the language to use for execution.
+    /// Sets the version of the language to use for execution. Any
+    /// version set previously is overwritten, use
+    /// [`Executor::try_set_version`] to catch conflicting versions.
     ///
     /// # Arguments
     /// - `version` - The version to use.
@@ -241,6 +1525,38 @@ impl Executor {
         self
     }
 
+    /// Sets the version of the language to use for execution, failing
+    /// if a different version was already set. The default version,
+    /// "*", and an empty version can always be overwritten.
+    ///
+    /// # Arguments
+    /// - `version` - The version to use.
+    ///
+    /// # Returns
+    /// - [`Result<Executor, PistonError>`] - The executor, for chained
+    ///   method calls, or a [`PistonError::InvalidExecutor`] if it
+    ///   already has a different version.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .try_set_version("1.50.0")
+    ///     .unwrap();
+    ///
+    /// assert!(executor.clone().try_set_version("1.50.0").is_ok());
+    /// assert!(executor.try_set_version("1.68.2").is_err());
+    /// ```
+    pub fn try_set_version(self, version: &str) -> Result<Self, PistonError> {
+        match self.version.as_str() {
+            "" | "*" => Ok(self.set_version(version)),
+            current if current == version => Ok(self),
+            current => Err(PistonError::InvalidExecutor(format!(
+                "Version is already set to {}, not setting it to {}",
+                current, version
+            ))),
+        }
+    }
+
     /// Adds a [`File`] containing the code to be executed. Does not
     /// overwrite any existing files.
     ///
@@ -266,7 +1582,8 @@ impl Executor {
     }
 
     /// Adds multiple [`File`]'s containing the code to be executed.
-    /// Does not overwrite any existing files.
+    /// Does not overwrite any existing files. The files keep their
+    /// order, so the first file added is still the main file.
     ///
     /// # Arguments
     /// - `files` - The files to add.
@@ -288,11 +1605,50 @@ impl Executor {
     /// assert_eq!(executor.files, files);
     /// ```
     #[must_use]
-    pub fn add_files(mut self, files: Vec<File>) -> Self {
+    pub fn add_files(mut self, files: impl IntoIterator<Item = File>) -> Self {
         self.files.extend(files);
         self
     }
 
+    /// Moves the file with the given name to the front, making it the
+    /// main file Piston runs. The order of the other files is kept.
+    ///
+    /// This matters for languages like Java, where the main file must
+    /// be named after its public class.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the main file.
+    ///
+    /// # Returns
+    /// - [`Result<Executor, PistonError>`] - The executor, for chained
+    ///   method calls, or a [`PistonError::InvalidExecutor`] if no file
+    ///   has that name.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("Util.java"))
+    ///     .add_file(piston_rs::File::default().set_name("Main.java"))
+    ///     .set_main("Main.java")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(executor.files[0].name, "Main.java".to_string());
+    /// assert!(executor.set_main("Missing.java").is_err());
+    /// ```
+    pub fn set_main(mut self, name: &str) -> Result<Self, PistonError> {
+        let index = self
+            .files
+            .iter()
+            .position(|f| f.name == name)
+            .ok_or_else(|| {
+                PistonError::InvalidExecutor(format!("No file named {} was added", name))
+            })?;
+
+        let main = self.files.remove(index);
+        self.files.insert(0, main);
+        Ok(self)
+    }
+
     /// Adds multiple [`File`]'s containing the code to be executed.
     /// Overwrites any existing files. This method mutates the existing
     /// executor in place. **Overwrites any existing files.**
@@ -311,19 +1667,66 @@ impl Executor {
     /// assert_eq!(executor.files.len(), 1);
     /// assert_eq!(executor.files[0].name, "old_file.rs".to_string());
     ///
-    /// let new_files = vec![
-    ///     piston_rs::File::default().set_name("new_file1.rs"),
-    ///     piston_rs::File::default().set_name("new_file2.rs"),
-    /// ];
+    /// let new_files = vec![
+    ///     piston_rs::File::default().set_name("new_file1.rs"),
+    ///     piston_rs::File::default().set_name("new_file2.rs"),
+    /// ];
+    ///
+    /// executor.set_files(new_files.clone());
+    ///
+    /// assert_eq!(executor.files.len(), 2);
+    /// assert_eq!(executor.files[0].name, "new_file1.rs".to_string());
+    /// assert_eq!(executor.files[1].name, "new_file2.rs".to_string());
+    /// ```
+    pub fn set_files(&mut self, files: Vec<File>) {
+        self.files = files;
+    }
+
+    /// Removes the first [`File`] with the given name, if any. If the
+    /// main file is removed, the next file becomes the main file.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the file to remove.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.rs"))
+    ///     .add_file(piston_rs::File::default().set_name("lib.rs"))
+    ///     .remove_file("main.rs");
+    ///
+    /// assert_eq!(executor.files.len(), 1);
+    /// assert_eq!(executor.files[0].name, "lib.rs".to_string());
+    /// ```
+    #[must_use]
+    pub fn remove_file(mut self, name: &str) -> Self {
+        if let Some(i) = self.files.iter().position(|f| f.name == name) {
+            self.files.remove(i);
+        }
+
+        self
+    }
+
+    /// Removes all files.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
     ///
-    /// executor.set_files(new_files.clone());
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default())
+    ///     .clear_files();
     ///
-    /// assert_eq!(executor.files.len(), 2);
-    /// assert_eq!(executor.files[0].name, "new_file1.rs".to_string());
-    /// assert_eq!(executor.files[1].name, "new_file2.rs".to_string());
+    /// assert!(executor.files.is_empty());
     /// ```
-    pub fn set_files(&mut self, files: Vec<File>) {
-        self.files = files;
+    #[must_use]
+    pub fn clear_files(mut self) -> Self {
+        self.files.clear();
+        self
     }
 
     /// Sets the text to pass as `stdin` to the program.
@@ -347,6 +1750,61 @@ impl Executor {
         self
     }
 
+    /// Sets raw bytes to pass as `stdin` to the program, base64
+    /// encoded.
+    ///
+    /// ##### Note
+    ///
+    /// Unlike files, Piston has no encoding option for `stdin`, so the
+    /// program receives the base64 text as is and must decode it
+    /// itself.
+    ///
+    /// # Arguments
+    /// - `bytes` - The bytes to set.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_stdin_bytes(&[0, 159, 146, 150]);
+    ///
+    /// assert_eq!(executor.stdin, "AJ+Slg==".to_string());
+    /// ```
+    #[must_use]
+    pub fn set_stdin_bytes(mut self, bytes: &[u8]) -> Self {
+        use base64::Engine;
+
+        self.stdin = base64::engine::general_purpose::STANDARD.encode(bytes);
+        self
+    }
+
+    /// Creates a copy of the executor with a different `stdin`,
+    /// leaving everything else unchanged.
+    ///
+    /// # Arguments
+    /// - `stdin` - The text to pass as `stdin` to the copy.
+    ///
+    /// # Returns
+    /// - [`Executor`] - The copy.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::from_content("python", "*", "print(input())");
+    /// let executors: Vec<_> = ["1", "2", "3"]
+    ///     .iter()
+    ///     .map(|stdin| executor.with_stdin(stdin))
+    ///     .collect();
+    ///
+    /// assert_eq!(executors[1].stdin, "2".to_string());
+    /// assert_eq!(executors[1].files, executor.files);
+    /// ```
+    #[must_use]
+    pub fn with_stdin(&self, stdin: &str) -> Self {
+        self.clone().set_stdin(stdin)
+    }
+
     /// Adds an arg to be passed as a command line argument. Does not
     /// overwrite any existing args.
     ///
@@ -499,10 +1957,64 @@ impl Executor {
     }
 }
 
+impl<'a> IntoIterator for &'a Executor {
+    type Item = &'a File;
+    type IntoIter = std::slice::Iter<'a, File>;
+
+    /// Iterates over the files of the executor, in the order they are
+    /// sent to Piston. The first file is the main file.
+    ///
+    /// # Returns
+    /// - [`std::slice::Iter<File>`] - The files.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.py"))
+    ///     .add_file(piston_rs::File::default().set_name("utils.py"));
+    ///
+    /// let names: Vec<_> = (&executor).into_iter().map(|f| f.name.as_str()).collect();
+    /// assert_eq!(names, vec!["main.py", "utils.py"]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.iter()
+    }
+}
+
+impl IntoIterator for Executor {
+    type Item = File;
+    type IntoIter = std::vec::IntoIter<File>;
+
+    /// Consumes the executor, iterating over its files in the order
+    /// they are sent to Piston. The first file is the main file.
+    ///
+    /// # Returns
+    /// - [`std::vec::IntoIter<File>`] - The files.
+    ///
+    /// # Example
+    /// ```
+    /// let executor = piston_rs::Executor::new()
+    ///     .add_file(piston_rs::File::default().set_name("main.py"));
+    ///
+    /// for file in executor {
+    ///     assert_eq!(file.name, "main.py".to_string());
+    /// }
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.into_iter()
+    }
+}
+
 #[cfg(test)]
 mod test_execution_result {
+    use super::diff_lines;
+    use super::AssertionFailure;
+    use super::DiffLine;
+    use super::ExecFailure;
     use super::ExecResponse;
     use super::ExecResult;
+    use super::Outcome;
+    use super::Signal;
 
     /// Generates an ExecResult for testing
     fn generate_result(stdout: &str, stderr: &str, code: isize) -> ExecResult {
@@ -512,6 +2024,9 @@ mod test_execution_result {
             output: format!("{}\n{}", stdout, stderr),
             code,
             signal: None,
+            stage_status: None,
+            message: None,
+            wall_time: None,
         }
     }
 
@@ -523,9 +2038,248 @@ mod test_execution_result {
             run: generate_result("Be unique.", "", 0),
             compile: None,
             status,
+            elapsed: None,
         }
     }
 
+    #[test]
+    fn test_into_result() {
+        let mut response = generate_response(200);
+        assert!(response.clone().into_result().is_ok());
+
+        response.run = generate_result("", "Traceback\n", 1);
+        let failure = response.clone().into_result().unwrap_err();
+        assert_eq!(failure.to_string(), "Run failed with code 1: Traceback");
+
+        response.run.signal = Some("SIGKILL".to_string());
+        response.run.stderr = String::new();
+        let failure = response.clone().into_result().unwrap_err();
+        assert_eq!(failure.to_string(), "Run failed with code 1 (SIGKILL)");
+
+        response.compile = Some(generate_result("", "error[E0425]", 1));
+        let failure = response.clone().into_result().unwrap_err();
+        assert_eq!(
+            failure,
+            ExecFailure::Compile {
+                code: 1,
+                stderr: "error[E0425]".to_string(),
+            }
+        );
+
+        response.status = 500;
+        let failure = response.into_result().unwrap_err();
+        assert_eq!(failure.to_string(), "Piston responded with status 500");
+    }
+
+    #[test]
+    fn test_compile_step() {
+        let mut response = generate_response(200);
+        assert!(!response.was_compiled());
+        assert_eq!(response.compile_succeeded(), None);
+
+        response.compile = Some(generate_result("", "", 0));
+        assert!(response.was_compiled());
+        assert_eq!(response.compile_succeeded(), Some(true));
+
+        response.compile = Some(generate_result("", "error", 1));
+        assert_eq!(response.compile_succeeded(), Some(false));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        use DiffLine::{Added, Removed, Unchanged};
+
+        let lines = diff_lines("a\nb\nc\n", "a\nc\nd\n");
+        assert_eq!(
+            lines,
+            vec![
+                Unchanged("a".to_string()),
+                Removed("b".to_string()),
+                Unchanged("c".to_string()),
+                Added("d".to_string()),
+            ]
+        );
+
+        assert_eq!(diff_lines("", "x"), vec![Added("x".to_string())]);
+        assert!(diff_lines("", "").is_empty());
+    }
+
+    #[test]
+    fn test_diff_lines_large() {
+        use DiffLine::{Added, Removed, Unchanged};
+
+        let old: String = (0..20000).map(|i| format!("{}\n", i)).collect();
+        let lines = diff_lines(&old, &old.replace("\n10000\n", "\nx\n"));
+
+        assert_eq!(lines.len(), 20001);
+        assert_eq!(lines[9999], Unchanged("9999".to_string()));
+        assert_eq!(lines[10000], Removed("10000".to_string()));
+        assert_eq!(lines[10001], Added("x".to_string()));
+        assert_eq!(lines[20000], Unchanged("19999".to_string()));
+
+        // Too many lines differ to diff them, so they're all replaced.
+        let new: String = (20000..40000).map(|i| format!("{}\n", i)).collect();
+        let lines = diff_lines(&format!("a\n{}", old), &format!("a\n{}", new));
+
+        assert_eq!(lines.len(), 40001);
+        assert_eq!(lines[0], Unchanged("a".to_string()));
+        assert!(lines[1..20001].iter().all(|l| matches!(l, Removed(_))));
+        assert!(lines[20001..].iter().all(|l| matches!(l, Added(_))));
+    }
+
+    #[test]
+    fn test_diff() {
+        let expected = generate_response(200);
+        let mut actual = generate_response(200);
+        actual.language = "python".to_string();
+        assert!(expected.diff(&actual).is_match());
+
+        actual.run = generate_result("Be unique.", "warning", 1);
+        let diff = expected.diff(&actual);
+        assert!(diff.stdout_matches);
+        assert!(!diff.stderr_matches);
+        assert!(!diff.exit_matches);
+        assert!(!diff.is_match());
+    }
+
+    #[test]
+    fn test_assert_stdout() {
+        let response = generate_response(200);
+        assert_eq!(response.assert_stdout("\nBe unique.  \n"), Ok(()));
+
+        let failure = response.assert_stdout("Be different.").unwrap_err();
+        assert_eq!(
+            failure,
+            AssertionFailure {
+                expected: "Be different.".to_string(),
+                actual: "Be unique.".to_string(),
+            }
+        );
+        assert_eq!(
+            failure.to_string(),
+            "stdout did not match the expected output\n- Be different.\n+ Be unique."
+        );
+    }
+
+    #[test]
+    fn test_step_output() {
+        let mut response = generate_response(200);
+        assert_eq!(response.compile_output(), None);
+        assert_eq!(response.run_output(), "Be unique.\n");
+
+        response.compile = Some(generate_result("", "error[E0425]", 1));
+        assert_eq!(response.compile_output(), Some("\nerror[E0425]"));
+    }
+
+    #[test]
+    fn test_stdout_trimmed() {
+        let mut response = generate_response(200);
+        response.run.stdout = "  42\n\n".to_string();
+
+        assert_eq!(response.run.stdout_trimmed(), "42");
+        assert_eq!(response.stdout(), "42");
+        assert_eq!(generate_result("", "", 0).stdout_trimmed(), "");
+    }
+
+    #[test]
+    fn test_possibly_truncated() {
+        let result = generate_result("12345", "", 0);
+
+        assert!(result.possibly_truncated(5));
+        assert!(!result.possibly_truncated(6));
+        assert!(generate_result("", "12345", 1).possibly_truncated(4));
+    }
+
+    #[test]
+    fn test_status_code() {
+        assert!(generate_response(200).status_code().is_success());
+        assert!(generate_response(503).status_code().is_server_error());
+        assert_eq!(generate_response(404).status_code().as_u16(), 404);
+        assert_eq!(generate_response(42).status_code().as_u16(), 500);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let mut response = generate_response(200);
+        assert_eq!(response.exit_code(), 0);
+
+        response.run.code = 2;
+        assert_eq!(response.exit_code(), 2);
+
+        response.compile = Some(generate_result("", "", 0));
+        assert_eq!(response.exit_code(), 2);
+
+        response.compile = Some(generate_result("", "error", 1));
+        assert_eq!(response.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_signal_kind() {
+        let mut result = generate_result("", "", 139);
+        assert_eq!(result.signal_kind(), None);
+
+        result.signal = Some("SIGSEGV".to_string());
+        assert_eq!(result.signal_kind(), Some(Signal::Segv));
+
+        result.signal = Some("SIGWINCH".to_string());
+        let signal = result.signal_kind().unwrap();
+        assert_eq!(signal, Signal::Other("SIGWINCH".to_string()));
+        assert_eq!(signal.to_string(), "SIGWINCH");
+        assert_eq!(Signal::Kill.to_string(), "SIGKILL");
+    }
+
+    #[test]
+    fn test_outcome() {
+        let mut response = generate_response(200);
+        assert_eq!(response.outcome(), Outcome::Success);
+
+        response.compile = Some(generate_result("", "", 0));
+        response.run = generate_result("", "", 137);
+        response.run.signal = Some("SIGKILL".to_string());
+        assert_eq!(
+            response.outcome(),
+            Outcome::RuntimeError {
+                code: 137,
+                signal: Some("SIGKILL".to_string()),
+            }
+        );
+
+        response.compile = Some(generate_result("", "error", 1));
+        assert_eq!(response.outcome(), Outcome::CompileError);
+
+        response.status = 400;
+        assert_eq!(response.outcome(), Outcome::ApiError { status: 400 });
+    }
+
+    #[test]
+    fn test_response_json_round_trip() {
+        let json = r#"{"language":"rust","version":"1.50.0","run":{"stdout":"42\n","stderr":"","output":"42\n","code":0,"signal":null},"compile":{"stdout":"","stderr":"","output":"","code":0,"signal":null},"status":200}"#;
+        let response = serde_json::from_str::<ExecResponse>(json).unwrap();
+
+        assert_eq!(response.run.stdout, "42\n");
+        assert!(response.compile.is_some());
+        assert_eq!(serde_json::to_string(&response).unwrap(), json);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut response = generate_response(200);
+
+        assert_eq!(format!("{}", response.run), "Be unique.\n");
+        assert_eq!(
+            format!("{}", response),
+            "rust 1.50.0\nBe unique.\nExited with code 0"
+        );
+
+        response.compile = Some(generate_result("", "error[E0425]", 1));
+        response.run.output = String::new();
+
+        assert_eq!(
+            format!("{}", response),
+            "rust 1.50.0\n\nerror[E0425]\nExited with code 1"
+        );
+    }
+
     #[test]
     fn test_response_is_ok() {
         let response = generate_response(200);
@@ -542,6 +2296,29 @@ mod test_execution_result {
         assert!(response.is_err());
     }
 
+    #[test]
+    fn test_response_is_err_compile_failed() {
+        let mut response = generate_response(200);
+        response.compile = Some(generate_result("", "error[E0425]", 1));
+
+        assert!(!response.is_ok());
+        assert!(response.is_err());
+
+        response.compile = Some(generate_result("", "", 0));
+
+        assert!(response.is_ok());
+        assert!(!response.is_err());
+    }
+
+    #[test]
+    fn test_response_is_err_run_failed() {
+        let mut response = generate_response(200);
+        response.run = generate_result("", "Traceback", 1);
+
+        assert!(!response.is_ok());
+        assert!(response.is_err());
+    }
+
     #[test]
     fn test_result_is_ok() {
         let result = generate_result("Hello, world", "", 0);
@@ -558,6 +2335,36 @@ mod test_execution_result {
         assert!(result.is_err());
     }
 
+    #[test]
+    #[cfg(feature = "color")]
+    fn test_render_without_color() {
+        let mut result = generate_result("Hello, world", "Error!", 137);
+        result.signal = Some("SIGKILL".to_string());
+
+        assert_eq!(
+            result.render(false),
+            "Hello, world\nError!\nExited with code 137 (SIGKILL)"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "color")]
+    fn test_render_with_color() {
+        let result = generate_result("Hello, world\n", "Error!", 1);
+
+        assert_eq!(
+            result.render(true),
+            "Hello, world\n\u{1b}[31mError!\u{1b}[39m\n\u{1b}[31mExited with code 1\u{1b}[39m"
+        );
+    }
+
+    #[test]
+    fn test_result_is_empty() {
+        assert!(generate_result("", "", 0).is_empty());
+        assert!(!generate_result("Hello, world", "", 0).is_empty());
+        assert!(!generate_result("", "Error!", 1).is_empty());
+    }
+
     #[test]
     fn test_is_err_with_stdout() {
         let result = generate_result("Hello, world", "Error!", 1);
@@ -566,3 +2373,249 @@ mod test_execution_result {
         assert!(result.is_err());
     }
 }
+
+#[cfg(test)]
+mod test_executor_serialization {
+    use super::Executor;
+    use super::File;
+
+    #[test]
+    fn test_json_round_trip() {
+        let executor = Executor::from_content("python", "3.10.0", "print(42)")
+            .set_stdin("42")
+            .add_arg("-v");
+        let json = serde_json::to_string(&executor).unwrap();
+
+        assert_eq!(Executor::from_json_str(&json).unwrap(), executor);
+        assert!(Executor::from_json_str(r#"{"language": 42}"#).is_err());
+    }
+
+    #[test]
+    fn test_set_files_serialized() {
+        let mut executor = Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_name("old.py"));
+
+        executor.set_files(vec![
+            File::default().set_name("main.py"),
+            File::default().set_name("util.py"),
+        ]);
+        let json = serde_json::to_value(&executor).unwrap();
+
+        assert_eq!(json["files"].as_array().unwrap().len(), 2);
+        assert_eq!(json["files"][0]["name"], "main.py");
+        assert_eq!(json["files"][1]["name"], "util.py");
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir = std::env::temp_dir().join(format!("piston_rs_dir_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("util.py"), "x = 42").unwrap();
+        std::fs::write(dir.join("main.py"), "import util").unwrap();
+        std::fs::write(dir.join("nested").join("skipped.py"), "").unwrap();
+
+        let executor = Executor::from_dir("python", "3.10.0", &dir);
+        let missing = Executor::from_dir("python", "3.10.0", dir.join("missing"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let executor = executor.unwrap();
+        let names: Vec<_> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["main.py", "util.py"]);
+        assert_eq!(executor.files[0].content, "import util");
+        assert_eq!(executor.version, "3.10.0");
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_path() {
+        let path = std::env::temp_dir().join(format!("piston_rs_{}.json", std::process::id()));
+        let executor = Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_content("aGk=").set_encoding("base64"));
+
+        executor.save_to_path(&path).unwrap();
+        let loaded = Executor::load_from_path(&path);
+        std::fs::write(&path, "not json").unwrap();
+        let invalid = Executor::load_from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), executor);
+        assert_eq!(invalid.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_from_content_matches_builder() {
+        let built = Executor::new()
+            .set_language("python")
+            .set_version("3.10.0")
+            .add_file(File::default().set_content("print(42)"));
+
+        assert_eq!(
+            Executor::from_content("python", "3.10.0", "print(42)"),
+            built
+        );
+    }
+
+    #[test]
+    fn test_args_serialized() {
+        let executor = Executor::new()
+            .add_arg("--verbose")
+            .add_args(vec!["-n", "3"]);
+        let json = serde_json::to_value(&executor).unwrap();
+
+        assert_eq!(json["args"], serde_json::json!(["--verbose", "-n", "3"]));
+    }
+
+    #[test]
+    fn test_empty_args_serialized() {
+        let executor = Executor::new();
+        let json = serde_json::to_value(&executor).unwrap();
+
+        assert_eq!(json["args"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_memory_limits_serialized() {
+        let json = serde_json::to_value(Executor::new()).unwrap();
+
+        assert_eq!(json["compile_memory_limit"], -1);
+        assert_eq!(json["run_memory_limit"], -1);
+
+        let executor = Executor::new()
+            .set_compile_memory_limit(200_000_000)
+            .set_run_memory_limit(100_000_000);
+        let json = serde_json::to_value(&executor).unwrap();
+
+        assert_eq!(json["compile_memory_limit"], 200_000_000);
+        assert_eq!(json["run_memory_limit"], 100_000_000);
+    }
+
+    #[test]
+    fn test_add_files_keeps_order() {
+        let executor = Executor::new()
+            .add_file(File::default().set_name("main.rs"))
+            .add_files(["a.rs", "b.rs", "c.rs"].map(|n| File::default().set_name(n)));
+        let json = serde_json::to_value(&executor).unwrap();
+
+        let names: Vec<_> = executor.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["main.rs", "a.rs", "b.rs", "c.rs"]);
+        assert_eq!(json["files"][0]["name"], "main.rs");
+        assert_eq!(json["files"][3]["name"], "c.rs");
+    }
+
+    #[test]
+    fn test_validate() {
+        let err = Executor::new().validate().unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid executor: No language was set");
+
+        let err = Executor::new()
+            .set_language("rust")
+            .set_version("")
+            .validate()
+            .unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid executor: No version was set");
+
+        let err = Executor::new().set_language("rust").validate().unwrap_err();
+        assert_eq!(format!("{}", err), "Invalid executor: No files were added");
+    }
+
+    #[test]
+    fn test_executor_as_map_key() {
+        let mut cache = std::collections::HashMap::new();
+        let executor = Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_content("print(42)"));
+
+        cache.insert(executor.clone(), "42");
+
+        assert_eq!(cache.get(&executor.clone()), Some(&"42"));
+        assert_eq!(cache.get(&executor.set_stdin("input")), None);
+    }
+
+    #[test]
+    fn test_iterate_files() {
+        let executor = Executor::new()
+            .add_file(File::from("main"))
+            .add_file(File::from("other"));
+        let mut contents = vec![];
+
+        for file in &executor {
+            contents.push(file.content.clone());
+        }
+
+        assert_eq!(contents, vec!["main", "other"]);
+        assert_eq!(executor.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_set_stdin_bytes() {
+        use base64::Engine;
+
+        let bytes = [0, 1, 2, 253, 254, 255];
+        let executor = Executor::new().set_stdin_bytes(&bytes);
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&executor.stdin)
+            .unwrap();
+
+        assert_eq!(decoded, bytes);
+        assert_eq!(Executor::new().set_stdin_bytes(&[]).stdin, "");
+    }
+
+    #[test]
+    fn test_set_main() {
+        let executor = Executor::new()
+            .add_file(File::default().set_name("a.java"))
+            .add_file(File::default().set_name("b.java"))
+            .add_file(File::default().set_name("Main.java"))
+            .set_main("Main.java")
+            .unwrap();
+        let names: Vec<_> = executor.files.iter().map(|f| f.name.as_str()).collect();
+
+        assert_eq!(names, vec!["Main.java", "a.java", "b.java"]);
+
+        let err = executor.set_main("Other.java").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid executor: No file named Other.java was added"
+        );
+    }
+
+    #[test]
+    fn test_default_serialized() {
+        let json = serde_json::to_value(Executor::default()).unwrap();
+        let fields = json.as_object().unwrap();
+
+        for field in [
+            "language",
+            "version",
+            "files",
+            "stdin",
+            "args",
+            "compile_timeout",
+            "run_timeout",
+            "compile_memory_limit",
+            "run_memory_limit",
+        ] {
+            assert!(fields.contains_key(field), "Missing {}", field);
+        }
+
+        assert_eq!(json["files"], serde_json::json!([]));
+        assert_eq!(Executor::default(), Executor::new());
+    }
+
+    #[test]
+    fn test_try_set_version() {
+        let executor = Executor::new().try_set_version("3.10.0").unwrap();
+        assert_eq!(executor.version, "3.10.0");
+
+        let err = executor.try_set_version("3.9.4").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid executor: Version is already set to 3.10.0, not setting it to 3.9.4"
+        );
+
+        let executor = Executor::new().set_version("").try_set_version("*");
+        assert_eq!(executor.unwrap().version, "*");
+    }
+}
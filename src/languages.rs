@@ -0,0 +1,175 @@
+/// The languages, and their aliases, provided by the packages of the
+/// public Piston instance.
+const KNOWN_LANGUAGES: &[(&str, &[&str])] = &[
+    ("awk", &[]),
+    ("bash", &["sh"]),
+    (
+        "basic.net",
+        &["vb", "vbnc", "visual-basic", "visual-basic.net"],
+    ),
+    ("befunge93", &["b93"]),
+    ("bqn", &[]),
+    ("brachylog", &[]),
+    ("brainfuck", &["bf"]),
+    ("c", &["gcc"]),
+    ("c++", &["cpp", "g++"]),
+    ("cjam", &[]),
+    ("clojure", &["clj"]),
+    ("cobol", &["cob"]),
+    ("coffeescript", &["coffee"]),
+    ("cow", &[]),
+    ("crystal", &["cr"]),
+    (
+        "csharp",
+        &["mono", "mono-csharp", "mono-c#", "mono-cs", "c#", "cs"],
+    ),
+    (
+        "csharp.net",
+        &[
+            "dotnet",
+            "dotnet-csharp",
+            "dotnet-c#",
+            "dotnet-cs",
+            "c#.net",
+            "cs.net",
+        ],
+    ),
+    ("d", &["dmd"]),
+    ("dart", &[]),
+    ("dash", &[]),
+    ("dragon", &[]),
+    ("elixir", &["exs"]),
+    ("emacs", &["el", "elisp"]),
+    ("emojicode", &["emojic"]),
+    ("erlang", &["erl", "escript"]),
+    ("file", &["executable", "elf", "binary"]),
+    ("forte", &["forter"]),
+    ("forth", &["gforth"]),
+    ("fortran", &["f90", "f95"]),
+    (
+        "freebasic",
+        &["bas", "fbc", "basic", "qbasic", "quickbasic"],
+    ),
+    ("fsharp.net", &["fsharp", "fs", "f#", "fs.net", "f#.net"]),
+    (
+        "fsi",
+        &[
+            "fsi.net",
+            "fsharp-interactive",
+            "fs-interactive",
+            "dotnet-fsi",
+            "fsx",
+            "fsscript",
+        ],
+    ),
+    ("go", &["golang"]),
+    ("golfscript", &[]),
+    ("groovy", &["gvy"]),
+    ("haskell", &["hs"]),
+    ("husk", &[]),
+    ("iverilog", &["verilog", "vvp"]),
+    ("japt", &[]),
+    ("java", &[]),
+    ("javascript", &["node-javascript", "node-js", "js"]),
+    ("jelly", &[]),
+    ("julia", &["jl"]),
+    ("kotlin", &["kt"]),
+    ("lisp", &["cl", "sbcl", "commonlisp"]),
+    ("llvm_ir", &["llvm", "llvm-ir", "ll"]),
+    ("lolcode", &["lol", "lci"]),
+    ("lua", &[]),
+    ("matl", &[]),
+    ("nasm", &["asm", "nasm32"]),
+    ("nasm64", &["asm64"]),
+    ("nim", &[]),
+    ("ocaml", &["ml"]),
+    ("octave", &["matlab", "m"]),
+    ("osabie", &["05AB1E", "usable"]),
+    ("paradoc", &[]),
+    ("pascal", &["freepascal", "pp", "pas"]),
+    ("perl", &["pl"]),
+    ("php", &[]),
+    ("ponylang", &["pony", "ponyc"]),
+    ("powershell", &["ps", "pwsh", "ps1"]),
+    ("prolog", &["plg"]),
+    ("pure", &[]),
+    ("pyth", &[]),
+    ("python", &["py", "py3", "python3", "python3.10"]),
+    ("python2", &["py2"]),
+    ("racket", &["rkt"]),
+    ("raku", &["rakudo", "perl6", "p6", "pl6"]),
+    ("retina", &[]),
+    ("rockstar", &["rock", "rocky"]),
+    ("rscript", &["r"]),
+    ("ruby", &["ruby3", "rb"]),
+    ("rust", &["rs"]),
+    ("samarium", &["sm"]),
+    ("scala", &["sc"]),
+    ("smalltalk", &["st"]),
+    ("sqlite3", &["sqlite", "sql"]),
+    ("swift", &[]),
+    ("tcl", &[]),
+    (
+        "typescript",
+        &["ts", "node-ts", "tsc", "typescript5", "ts5"],
+    ),
+    ("vlang", &["v"]),
+    ("vyxal", &[]),
+    ("yeethon", &["yeethon3"]),
+    ("zig", &[]),
+];
+
+/// Whether or not the given name is a language, or an alias of one,
+/// known to Piston. The comparison is case insensitive.
+///
+/// ##### Note
+///
+/// This is a best effort check against a list of the languages
+/// available on the public Piston instance, bundled with this crate.
+/// It is meant for catching typos early, without making a request.
+/// Self-hosted instances may have different languages installed, and
+/// the public instance may add more over time, so use
+/// [`crate::Client::fetch_runtimes`] when you need to know for sure.
+///
+/// # Arguments
+/// - `name` - The language name or alias to check.
+///
+/// # Returns
+/// - [`bool`] - [`true`] if the name is a known language or alias.
+///
+/// # Example
+/// ```
+/// assert!(piston_rs::is_known_language("python"));
+/// assert!(piston_rs::is_known_language("RS"));
+/// assert!(!piston_rs::is_known_language("pyhton"));
+/// ```
+pub fn is_known_language(name: &str) -> bool {
+    KNOWN_LANGUAGES.iter().any(|(language, aliases)| {
+        language.eq_ignore_ascii_case(name) || aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    })
+}
+
+#[cfg(test)]
+mod test_languages_private {
+    use super::is_known_language;
+    use super::KNOWN_LANGUAGES;
+
+    #[test]
+    fn test_known_languages_sorted_and_unique() {
+        let languages: Vec<_> = KNOWN_LANGUAGES.iter().map(|(l, _)| *l).collect();
+        let mut sorted = languages.clone();
+        sorted.sort();
+        sorted.dedup();
+
+        assert_eq!(languages, sorted);
+    }
+
+    #[test]
+    fn test_is_known_language() {
+        assert!(is_known_language("c++"));
+        assert!(is_known_language("Cpp"));
+        assert!(is_known_language("05ab1e"));
+        assert!(!is_known_language(""));
+        assert!(!is_known_language("javscript"));
+    }
+}
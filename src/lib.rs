@@ -8,6 +8,26 @@
 //!
 //! Check out the [`Client`] and [`Executor`] documentation.
 //!
+//! If you are not using async, enable the `blocking` feature and use
+//! `piston_rs::blocking::Client` instead.
+//!
+//! TLS is provided by `rustls` through the default `rustls-tls`
+//! feature. Disable the default features and enable `native-tls` to
+//! use the platform's native TLS instead.
+//!
+//! Enable the `gzip` and/or `brotli` features to receive compressed
+//! responses from Piston, which are decompressed transparently.
+//!
+//! Enable the `tracing` feature to emit `tracing` spans and events
+//! for each request [`Client`] sends to Piston.
+//!
+//! Enable the `ws` feature to stream output from self-hosted Piston
+//! instances as it is produced, using `Client::execute_streaming`.
+//!
+//! To test code that talks to Piston without making http requests,
+//! write it against [`PistonBackend`] and enable the `mock` feature to
+//! use `piston_rs::mock::MockClient` in your tests.
+//!
 //! ##### Make requests to Piston
 //!
 //! ```
@@ -46,15 +66,43 @@
 
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
+mod backend;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod client;
+mod error;
 mod executor;
+mod languages;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "ws")]
+mod stream;
+#[cfg(feature = "tracing")]
+mod trace;
 
+pub use backend::PistonBackend;
+pub use client::merge_runtimes;
+pub use client::CacheSnapshot;
 pub use client::Client;
+pub use client::ClientBuilder;
+pub use client::RateLimit;
+pub use client::RetryPolicy;
+pub use error::PistonError;
+pub use executor::AssertionFailure;
+pub use executor::DiffLine;
+pub use executor::ExecFailure;
 pub use executor::ExecResponse;
 pub use executor::ExecResult;
 pub use executor::Executor;
+pub use executor::Outcome;
+pub use executor::OutputDiff;
+pub use executor::Signal;
+pub use languages::is_known_language;
+#[cfg(feature = "ws")]
+pub use stream::OutputChunk;
 
 /// A runtime available to be used by Piston.
 ///
@@ -63,7 +111,11 @@ pub use executor::Executor;
 /// Runtimes are not meant to be created manually. Instead, they should
 /// be fetched from Piston using [`Client::fetch_runtimes`] and stored,
 /// if you have a need for the information.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// Runtimes compare equal, and hash the same, when their language,
+/// version and aliases are all equal, so they can be deduplicated with
+/// a [`std::collections::HashSet`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Runtime {
     /// The language.
     pub language: String,
@@ -73,6 +125,133 @@ pub struct Runtime {
     pub aliases: Vec<String>,
 }
 
+impl Runtime {
+    /// Whether or not the given name refers to this runtime's
+    /// language, either directly or by one of its aliases. The
+    /// comparison is case insensitive.
+    ///
+    /// # Arguments
+    /// - `name` - The language name or alias to check.
+    ///
+    /// # Returns
+    /// - [`bool`] - [`true`] if the name matches.
+    ///
+    /// # Example
+    /// ```
+    /// let rt = piston_rs::Runtime {
+    ///     language: "python".to_string(),
+    ///     version: "3.10.0".to_string(),
+    ///     aliases: vec!["py".to_string()],
+    /// };
+    ///
+    /// assert!(rt.matches("python"));
+    /// assert!(rt.matches("PY"));
+    /// assert!(!rt.matches("rust"));
+    /// ```
+    pub fn matches(&self, name: &str) -> bool {
+        self.language.eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+
+    /// The version of this runtime, parsed as a semantic version.
+    ///
+    /// # Returns
+    /// - [`Option<semver::Version>`] - The parsed version, or [`None`]
+    ///   if it is not valid semver.
+    ///
+    /// # Example
+    /// ```
+    /// let rt = piston_rs::Runtime {
+    ///     language: "python".to_string(),
+    ///     version: "3.10.0".to_string(),
+    ///     aliases: vec![],
+    /// };
+    ///
+    /// assert_eq!(rt.version_semver(), Some(semver::Version::new(3, 10, 0)));
+    /// ```
+    pub fn version_semver(&self) -> Option<semver::Version> {
+        semver::Version::parse(&self.version).ok()
+    }
+}
+
+/// Formats runtimes as a table, with aligned columns for the language,
+/// version and aliases, i.e. for listing the languages available in a
+/// command line tool.
+///
+/// # Arguments
+/// - `runtimes` - The runtimes to format, in the order to list them.
+///
+/// # Returns
+/// - [`String`] - The table, with a header row.
+///
+/// # Example
+/// ```
+/// let runtimes = vec![
+///     piston_rs::Runtime {
+///         language: "python".to_string(),
+///         version: "3.10.0".to_string(),
+///         aliases: vec!["py".to_string(), "py3".to_string()],
+///     },
+///     piston_rs::Runtime {
+///         language: "bash".to_string(),
+///         version: "5.2.0".to_string(),
+///         aliases: vec![],
+///     },
+/// ];
+///
+/// assert_eq!(
+///     piston_rs::format_runtimes_table(&runtimes),
+///     "Language  Version  Aliases\n\
+///      python    3.10.0   py, py3\n\
+///      bash      5.2.0\n"
+/// );
+/// ```
+pub fn format_runtimes_table(runtimes: &[Runtime]) -> String {
+    let rows: Vec<[String; 3]> =
+        std::iter::once(["Language", "Version", "Aliases"].map(String::from))
+            .chain(runtimes.iter().map(|rt| {
+                [
+                    rt.language.clone(),
+                    rt.version.clone(),
+                    rt.aliases.join(", "),
+                ]
+            }))
+            .collect();
+
+    let width = |column: usize| {
+        rows.iter()
+            .map(|row| row[column].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (language, version) = (width(0), width(1));
+
+    rows.iter()
+        .map(|[l, v, a]| {
+            let row = format!("{:language$}  {:version$}  {}", l, v, a);
+            format!("{}\n", row.trim_end())
+        })
+        .collect()
+}
+
+/// A language package known to a Piston instance.
+///
+/// ##### Note
+///
+/// Packages are not meant to be created manually. Instead, they should
+/// be fetched from Piston using [`Client::fetch_packages`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package {
+    /// The language.
+    pub language: String,
+    /// The version of the language.
+    #[serde(alias = "version")]
+    pub language_version: String,
+    /// Whether or not the package is installed.
+    #[serde(default)]
+    pub installed: bool,
+}
+
 /// The result from attempting to load a [`File`].
 type LoadResult<T> = Result<T, LoadError>;
 
@@ -112,15 +291,26 @@ impl std::fmt::Display for LoadError {
     }
 }
 
+/// The encodings Piston supports for file content.
+const ENCODINGS: [&str; 3] = ["utf8", "base64", "hex"];
+
 /// A file that contains source code to be executed.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+///
+/// When deserializing, missing fields take their default values.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct File {
     // The name of the file. Defaults to a new `String`.
     pub name: String,
     /// **Required** The content of the file.
     pub content: String,
-    /// The encoding of the file. Defaults to "utf8".
+    /// The encoding of the file. One of "utf8", "base64", or "hex".
+    /// Defaults to "utf8".
     pub encoding: String,
+    /// The character set of the content, i.e. "Shift_JIS", if it is
+    /// not UTF-8. Only sent when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charset: Option<String>,
 }
 
 impl Default for File {
@@ -137,16 +327,64 @@ impl Default for File {
     /// assert_eq!(file.name, String::new());
     /// assert_eq!(file.content, String::new());
     /// assert_eq!(file.encoding, "utf8".to_string());
+    /// assert_eq!(file.charset, None);
     /// ```
     fn default() -> Self {
         Self {
             name: String::new(),
             content: String::new(),
             encoding: String::from("utf8"),
+            charset: None,
         }
     }
 }
 
+impl From<&str> for File {
+    /// Creates an unnamed new [`File`] with utf8 encoding and the given
+    /// content.
+    ///
+    /// # Arguments
+    /// - `content` - The content of the file.
+    ///
+    /// # Returns
+    /// - [`File`] - The new File.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::from("print(42)");
+    ///
+    /// assert_eq!(file.name, String::new());
+    /// assert_eq!(file.content, "print(42)".to_string());
+    /// assert_eq!(file.encoding, "utf8".to_string());
+    /// ```
+    fn from(content: &str) -> Self {
+        Self::default().set_content(content)
+    }
+}
+
+impl std::str::FromStr for File {
+    type Err = std::convert::Infallible;
+
+    /// Creates an unnamed new [`File`] with utf8 encoding and the given
+    /// content. This never fails.
+    ///
+    /// # Arguments
+    /// - `content` - The content of the file.
+    ///
+    /// # Returns
+    /// - [`Result<File, std::convert::Infallible>`] - The new File.
+    ///
+    /// # Example
+    /// ```
+    /// let file: piston_rs::File = "print(42)".parse().unwrap();
+    ///
+    /// assert_eq!(file, piston_rs::File::from("print(42)"));
+    /// ```
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(content))
+    }
+}
+
 impl File {
     /// Creates a new [`File`].
     ///
@@ -154,7 +392,7 @@ impl File {
     /// - `name` - The name to use.
     /// - `content` - The content to use.
     /// - `encoding` - The encoding to use. Must be one of "utf8",
-    /// "hex", or "base64".
+    ///   "hex", or "base64".
     ///
     /// # Returns
     /// - [`File`] - The new File.
@@ -176,6 +414,7 @@ impl File {
             name: name.to_string(),
             content: content.to_string(),
             encoding: encoding.to_string(),
+            charset: None,
         }
     }
 
@@ -213,6 +452,46 @@ impl File {
             name: name.to_string(),
             content: File::load_contents(&path)?,
             encoding: String::from("utf8"),
+            charset: None,
+        })
+    }
+
+    /// Creates a new [`File`] from an existing file on disk, returning
+    /// the io error if it cannot be read.
+    ///
+    /// # Arguments
+    /// - `path` - The path to the file.
+    ///
+    /// # Returns
+    /// - [`std::io::Result<File>`] - The new File, or the error.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::from_path("src/lib.rs").unwrap();
+    ///
+    /// assert!(file.content.contains("pub fn from_path"));
+    /// assert_eq!(file.name, "lib.rs".to_string());
+    /// assert_eq!(file.encoding, "utf8".to_string());
+    ///
+    /// assert!(piston_rs::File::from_path("/path/doesnt/exist").is_err());
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let name = match path.file_name() {
+            Some(n) => n.to_string_lossy(),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Unable to parse file name",
+                ));
+            }
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            content: fs::read_to_string(path)?,
+            encoding: String::from("utf8"),
+            charset: None,
         })
     }
 
@@ -237,6 +516,35 @@ impl File {
         }
     }
 
+    /// Creates a new [`File`] from raw bytes, like a compiled binary,
+    /// which are base64 encoded so they can be sent to Piston.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the file.
+    /// - `bytes` - The content of the file.
+    ///
+    /// # Returns
+    /// - [`File`] - The new File, with "base64" encoding.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::from_bytes("fixture.bin", &[0, 159, 146, 150]);
+    ///
+    /// assert_eq!(file.name, "fixture.bin".to_string());
+    /// assert_eq!(file.content, "AJ+Slg==".to_string());
+    /// assert_eq!(file.encoding, "base64".to_string());
+    /// ```
+    pub fn from_bytes(name: &str, bytes: &[u8]) -> Self {
+        use base64::Engine;
+
+        Self {
+            name: name.to_string(),
+            content: base64::engine::general_purpose::STANDARD.encode(bytes),
+            encoding: String::from("base64"),
+            charset: None,
+        }
+    }
+
     /// Sets the content of the file.
     ///
     /// # Arguments
@@ -302,11 +610,12 @@ impl File {
         self
     }
 
-    /// Sets the encoding of the file.
+    /// Sets the encoding of the file. Unknown encodings are ignored,
+    /// leaving the current encoding unchanged.
     ///
     /// # Arguments
     /// - `encoding` - The encoding to use. Must be one of "utf8",
-    /// "hex", or "base64".
+    ///   "hex", or "base64".
     ///
     /// # Returns
     /// - [`Self`] - For chained method calls.
@@ -317,10 +626,43 @@ impl File {
     ///     .set_encoding("hex");
     ///
     /// assert_eq!(file.encoding, "hex".to_string());
+    ///
+    /// let file = file.set_encoding("utf16");
+    ///
+    /// assert_eq!(file.encoding, "hex".to_string());
     /// ```
     #[must_use]
     pub fn set_encoding(mut self, encoding: &str) -> Self {
-        self.encoding = encoding.to_string();
+        if ENCODINGS.contains(&encoding) {
+            self.encoding = encoding.to_string();
+        }
+
+        self
+    }
+
+    /// Sets a hint for the character set of the file's content.
+    ///
+    /// ##### Note
+    /// Piston itself assumes source files are UTF-8 and ignores this,
+    /// so it is only useful with instances that support it. Everywhere
+    /// else, setting it does nothing.
+    ///
+    /// # Arguments
+    /// - `charset` - The character set, i.e. "Shift_JIS".
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    ///
+    /// # Example
+    /// ```
+    /// let file = piston_rs::File::default()
+    ///     .set_charset("Shift_JIS");
+    ///
+    /// assert_eq!(file.charset, Some("Shift_JIS".to_string()));
+    /// ```
+    #[must_use]
+    pub fn set_charset(mut self, charset: &str) -> Self {
+        self.charset = Some(charset.to_string());
         self
     }
 }
@@ -358,6 +700,39 @@ mod test_file_private {
         assert_eq!(err.details, err2.details);
     }
 
+    #[test]
+    fn test_from_path_no_file_name() {
+        let err = File::from_path("..").unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_from_bytes_round_trip() {
+        use base64::Engine;
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        let file = File::from_bytes("all.bin", &bytes);
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&file.content)
+            .unwrap();
+
+        assert_eq!(decoded, bytes);
+        assert_eq!(file.encoding, "base64");
+    }
+
+    #[test]
+    fn test_charset_serialization() {
+        let file = File::from("print(42)");
+        let json = serde_json::to_value(&file).unwrap();
+        assert!(json.get("charset").is_none());
+
+        let file = file.set_charset("Shift_JIS");
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(json.contains(r#""charset":"Shift_JIS""#));
+        assert_eq!(serde_json::from_str::<File>(&json).unwrap(), file);
+    }
+
     #[test]
     fn test_runtime_creation() {
         let rt = Runtime {
@@ -375,4 +750,54 @@ mod test_file_private {
         assert_eq!(rt.version, "9000".to_string());
         assert!(rt.aliases.is_empty());
     }
+
+    #[test]
+    fn test_runtime_json_round_trip() {
+        let json = r#"{"language":"python","version":"3.10.0","aliases":["py","py3"]}"#;
+        let rt = serde_json::from_str::<Runtime>(json).unwrap();
+
+        assert_eq!(rt.aliases, vec!["py".to_string(), "py3".to_string()]);
+        assert_eq!(serde_json::to_string(&rt).unwrap(), json);
+    }
+
+    #[test]
+    fn test_runtime_hash_set() {
+        let rt = Runtime {
+            language: "python".to_string(),
+            version: "3.10.0".to_string(),
+            aliases: vec!["py".to_string()],
+        };
+        let mut other = rt.clone();
+        other.aliases.push("py3".to_string());
+
+        let runtimes: std::collections::HashSet<_> =
+            vec![rt.clone(), rt.clone(), other].into_iter().collect();
+
+        assert_eq!(runtimes.len(), 2);
+        assert!(runtimes.contains(&rt));
+    }
+
+    #[test]
+    fn test_format_runtimes_table() {
+        let runtime = |language: &str, version: &str, aliases: &[&str]| Runtime {
+            language: language.to_string(),
+            version: version.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        };
+        let table = super::format_runtimes_table(&[
+            runtime("c", "10.2.0", &["gcc"]),
+            runtime("typescript", "5.0.3", &[]),
+        ]);
+
+        assert_eq!(
+            table,
+            "Language    Version  Aliases\n\
+             c           10.2.0   gcc\n\
+             typescript  5.0.3\n"
+        );
+        assert_eq!(
+            super::format_runtimes_table(&[]),
+            "Language  Version  Aliases\n"
+        );
+    }
 }
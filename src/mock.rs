@@ -0,0 +1,246 @@
+//! A mock backend, for testing code that talks to Piston without
+//! making any http requests.
+//!
+//! Requires the `mock` feature to be enabled.
+//!
+//! ##### Test code that uses Piston
+//!
+//! ```
+//! # #[tokio::test]
+//! # async fn example() {
+//! use piston_rs::PistonBackend;
+//!
+//! let mock = piston_rs::mock::MockClient::new()
+//!     .add_response(piston_rs::mock::MockClient::success("42\n"));
+//!
+//! let executor = piston_rs::Executor::new()
+//!     .set_language("python")
+//!     .add_file(piston_rs::File::default().set_content("print(42)"));
+//!
+//! let response = mock.execute(&executor).await.unwrap();
+//!
+//! assert_eq!(response.run.stdout, "42\n".to_string());
+//! assert_eq!(mock.executed().len(), 1);
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::future::{ready, Future};
+use std::sync::{Arc, Mutex};
+
+use super::ExecResponse;
+use super::ExecResult;
+use super::Executor;
+use super::PistonBackend;
+use super::PistonError;
+use super::Runtime;
+
+/// A [`PistonBackend`] that returns canned runtimes and responses.
+///
+/// Responses are returned in the order they were added, and the last
+/// one is repeated once the rest have been used. Clones of a mock
+/// share its responses and the record of what was executed.
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    /// The runtimes to return.
+    runtimes: Vec<Runtime>,
+    /// The responses to return, in order.
+    responses: Arc<Mutex<VecDeque<ExecResponse>>>,
+    /// The executors that have been executed.
+    executed: Arc<Mutex<Vec<Executor>>>,
+}
+
+impl MockClient {
+    /// Creates a new mock with no runtimes or responses.
+    ///
+    /// # Returns
+    /// - [`MockClient`] - The new mock.
+    ///
+    /// # Example
+    /// ```
+    /// let mock = piston_rs::mock::MockClient::new();
+    ///
+    /// assert!(mock.executed().is_empty());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the runtimes the mock returns.
+    ///
+    /// # Arguments
+    /// - `runtimes` - The runtimes to return.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn set_runtimes(mut self, runtimes: Vec<Runtime>) -> Self {
+        self.runtimes = runtimes;
+        self
+    }
+
+    /// Adds a response for the mock to return.
+    ///
+    /// # Arguments
+    /// - `response` - The response to return.
+    ///
+    /// # Returns
+    /// - [`Self`] - For chained method calls.
+    #[must_use]
+    pub fn add_response(self, response: ExecResponse) -> Self {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.push_back(response);
+        }
+
+        self
+    }
+
+    /// The executors the mock has been asked to execute, in order.
+    ///
+    /// # Returns
+    /// - [`Vec<Executor>`] - The executed executors.
+    pub fn executed(&self) -> Vec<Executor> {
+        match self.executed.lock() {
+            Ok(executed) => executed.clone(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Generates a successful response with the given `stdout`.
+    ///
+    /// # Arguments
+    /// - `stdout` - The text the program wrote to `stdout`.
+    ///
+    /// # Returns
+    /// - [`ExecResponse`] - The new response.
+    ///
+    /// # Example
+    /// ```
+    /// let response = piston_rs::mock::MockClient::success("42\n");
+    ///
+    /// assert!(response.is_ok());
+    /// assert_eq!(response.run.output, "42\n".to_string());
+    /// ```
+    pub fn success(stdout: &str) -> ExecResponse {
+        ExecResponse {
+            language: "mock".to_string(),
+            version: "0.0.0".to_string(),
+            run: ExecResult {
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+                output: stdout.to_string(),
+                code: 0,
+                signal: None,
+                stage_status: None,
+                message: None,
+                wall_time: None,
+            },
+            compile: None,
+            status: 200,
+            elapsed: None,
+        }
+    }
+
+    /// Records the executor and picks the next response.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor being executed.
+    ///
+    /// # Returns
+    /// - [`Result<ExecResponse, PistonError>`] - The next response, or
+    ///   an error if the executor is invalid or no responses were
+    ///   added.
+    fn next_response(&self, executor: &Executor) -> Result<ExecResponse, PistonError> {
+        executor.validate()?;
+
+        if let Ok(mut executed) = self.executed.lock() {
+            executed.push(executor.clone());
+        }
+
+        let mut responses = self.responses.lock().ok();
+        let response = match responses.as_mut() {
+            Some(r) if r.len() > 1 => r.pop_front(),
+            Some(r) => r.front().cloned(),
+            None => None,
+        };
+
+        response.ok_or_else(|| PistonError::Api {
+            status: 501,
+            message: "MockClient has no responses".to_string(),
+        })
+    }
+}
+
+impl PistonBackend for MockClient {
+    fn fetch_runtimes(&self) -> impl Future<Output = Result<Vec<Runtime>, PistonError>> + Send {
+        ready(Ok(self.runtimes.clone()))
+    }
+
+    fn execute(
+        &self,
+        executor: &Executor,
+    ) -> impl Future<Output = Result<ExecResponse, PistonError>> + Send {
+        ready(self.next_response(executor))
+    }
+}
+
+#[cfg(test)]
+mod test_mock_private {
+    use super::MockClient;
+    use crate::Executor;
+    use crate::File;
+    use crate::PistonBackend;
+    use crate::PistonError;
+    use crate::Runtime;
+
+    fn executor() -> Executor {
+        Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_content("print(42)"))
+    }
+
+    #[tokio::test]
+    async fn test_responses_in_order() {
+        let mock = MockClient::new()
+            .add_response(MockClient::success("1"))
+            .add_response(MockClient::success("2"));
+
+        let first = mock.execute(&executor()).await.unwrap();
+        let second = mock.clone().execute(&executor()).await.unwrap();
+        let third = mock.execute(&executor()).await.unwrap();
+
+        assert_eq!(first.run.stdout, "1");
+        assert_eq!(second.run.stdout, "2");
+        assert_eq!(third.run.stdout, "2");
+        assert_eq!(mock.executed().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_responses() {
+        let mock = MockClient::new();
+        let result = mock.execute(&executor()).await;
+
+        assert!(matches!(result, Err(PistonError::Api { status: 501, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_executor() {
+        let mock = MockClient::new().add_response(MockClient::success("1"));
+        let result = mock.execute(&Executor::new()).await;
+
+        assert!(matches!(result, Err(PistonError::InvalidExecutor(_))));
+        assert!(mock.executed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runtimes() {
+        let mock = MockClient::new().set_runtimes(vec![Runtime {
+            language: "python".to_string(),
+            version: "3.10.0".to_string(),
+            aliases: vec![],
+        }]);
+
+        let runtimes = mock.fetch_runtimes().await.unwrap();
+        assert_eq!(runtimes[0].language, "python");
+    }
+}
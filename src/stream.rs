@@ -0,0 +1,432 @@
+//! Streaming execution over Piston's WebSocket api.
+//!
+//! Requires the `ws` feature to be enabled.
+
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::Client;
+use super::Executor;
+use super::PistonError;
+
+/// The close code Piston uses once the job has completed.
+const JOB_COMPLETED: u16 = 4999;
+
+/// A piece of output from a program being executed by
+/// [`Client::execute_streaming`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputChunk {
+    /// The stream the output was written to, i.e. "stdout" or
+    /// "stderr".
+    pub stream: String,
+    /// The output.
+    pub data: Vec<u8>,
+}
+
+/// A message sent by Piston over the WebSocket.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event {
+    /// Output from the program.
+    Data { stream: String, data: String },
+    /// Something went wrong running the job.
+    Error { message: String },
+    /// Any other message, like the runtime or stage changing.
+    #[serde(other)]
+    Other,
+}
+
+impl Client {
+    /// Executes code using a given executor, streaming its output as
+    /// it is produced. **This is a WebSocket connection**.
+    ///
+    /// This uses the `/connect` endpoint, which the public Piston
+    /// instance does not expose. The stream ends once the job has
+    /// completed. If Piston closes the connection because of an error,
+    /// a [`PistonError::Api`] with the close code is returned last.
+    ///
+    /// Unlike [`Client::execute_with_timeout`], output is received as
+    /// it is produced, so none of it is lost if a program hangs. Use
+    /// [`Client::execute_streaming_with_timeout`] to collect the output
+    /// and keep what was received before a timeout.
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    ///
+    /// # Returns
+    /// - [`Result<impl Stream<Item = Result<OutputChunk, PistonError>>, PistonError>`] -
+    ///   The output as it is produced, or the error if the connection
+    ///   could not be made.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_streaming() {
+    /// use futures_util::StreamExt;
+    ///
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)"));
+    ///
+    /// if let Ok(mut chunks) = client.execute_streaming(&executor).await {
+    ///     while let Some(Ok(chunk)) = chunks.next().await {
+    ///         assert_eq!(chunk.stream, "stdout".to_string());
+    ///     }
+    /// } else {
+    ///     // There was an error contacting Piston.
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_streaming(
+        &self,
+        executor: &Executor,
+    ) -> Result<impl Stream<Item = Result<OutputChunk, PistonError>> + Send + Unpin, PistonError>
+    {
+        executor.validate()?;
+
+        let mut request = ws_url(&self.get_url()).into_client_request()?;
+        request.headers_mut().extend(self.get_headers());
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(request).await?;
+        socket.send(Message::Text(init_message(executor)?)).await?;
+
+        if !executor.stdin.is_empty() {
+            let stdin = serde_json::json!({
+                "type": "data",
+                "stream": "stdin",
+                "data": executor.stdin,
+            });
+
+            socket.send(Message::Text(stdin.to_string())).await?;
+        }
+
+        let chunks = stream::unfold(Some((socket, None)), |state| async move {
+            let (mut socket, mut error) = state?;
+
+            loop {
+                let text = match socket.next().await? {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(frame)) => {
+                        return close_error(frame, error).map(|e| (Err(e), None));
+                    }
+                    Ok(_) => continue,
+                    Err(e) => return Some((Err(e.into()), None)),
+                };
+
+                match serde_json::from_str::<Event>(&text) {
+                    Ok(Event::Data { stream, data }) => {
+                        let chunk = OutputChunk {
+                            stream,
+                            data: data.into_bytes(),
+                        };
+
+                        return Some((Ok(chunk), Some((socket, error))));
+                    }
+                    Ok(Event::Error { message }) => error = Some(message),
+                    Ok(Event::Other) => {}
+                    Err(e) => return Some((Err(e.into()), None)),
+                }
+            }
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    /// Executes code using a given executor, collecting its output
+    /// until the job completes or the timeout elapses. **This is a
+    /// WebSocket connection**.
+    ///
+    /// The timeout covers the whole execution, from connecting until
+    /// Piston closes the connection. When it elapses, a
+    /// [`PistonError::StreamTimeout`] is returned with every chunk
+    /// received so far, so the output of a hung program is not lost
+    /// like it is with [`Client::execute_with_timeout`].
+    ///
+    /// # Arguments
+    /// - `executor` - The executor to use.
+    /// - `timeout` - The maximum time to wait for the job to complete.
+    ///
+    /// # Returns
+    /// - [`Result<Vec<OutputChunk>, PistonError>`] - The output, or the
+    ///   error, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[tokio::test]
+    /// # async fn test_execute_streaming_with_timeout() {
+    /// use std::time::Duration;
+    ///
+    /// let client = piston_rs::Client::with_url("http://localhost:2000/api/v2");
+    /// let executor = piston_rs::Executor::new()
+    ///     .set_language("python")
+    ///     .add_file(piston_rs::File::default().set_content("print(42)\nwhile True: pass"));
+    ///
+    /// match client
+    ///     .execute_streaming_with_timeout(&executor, Duration::from_secs(5))
+    ///     .await
+    /// {
+    ///     Ok(chunks) => println!("Completed with {} chunks", chunks.len()),
+    ///     Err(piston_rs::PistonError::StreamTimeout { partial, .. }) => {
+    ///         assert_eq!(partial[0].data, b"42\n".to_vec());
+    ///     }
+    ///     Err(_) => {
+    ///         // There was an error contacting Piston.
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn execute_streaming_with_timeout(
+        &self,
+        executor: &Executor,
+        timeout: Duration,
+    ) -> Result<Vec<OutputChunk>, PistonError> {
+        let started = tokio::time::Instant::now();
+        let deadline = started + timeout;
+        let timed_out = |partial| PistonError::StreamTimeout {
+            endpoint: ws_url(&self.get_url()),
+            elapsed: started.elapsed(),
+            partial,
+        };
+
+        let mut chunks =
+            match tokio::time::timeout_at(deadline, self.execute_streaming(executor)).await {
+                Ok(chunks) => chunks?,
+                Err(_) => return Err(timed_out(vec![])),
+            };
+        let mut partial = vec![];
+
+        loop {
+            match tokio::time::timeout_at(deadline, chunks.next()).await {
+                Ok(Some(chunk)) => partial.push(chunk?),
+                Ok(None) => return Ok(partial),
+                Err(_) => return Err(timed_out(partial)),
+            }
+        }
+    }
+}
+
+/// Converts the http url of Piston into the url of its WebSocket
+/// endpoint.
+///
+/// # Arguments
+/// - `url` - The base url for Piston.
+///
+/// # Returns
+/// - [`String`] - The WebSocket url.
+fn ws_url(url: &str) -> String {
+    let url = match url.strip_prefix("http") {
+        Some(rest) => format!("ws{}", rest),
+        None => url.to_string(),
+    };
+
+    format!("{}/connect", url)
+}
+
+/// Generates the message that starts the job.
+///
+/// # Arguments
+/// - `executor` - The executor to use.
+///
+/// # Returns
+/// - [`Result<String, PistonError>`] - The message, or the error if
+///   the executor could not be serialized.
+fn init_message(executor: &Executor) -> Result<String, PistonError> {
+    let mut message = serde_json::to_value(executor)?;
+
+    if let Some(fields) = message.as_object_mut() {
+        // Stdin is sent separately, once the job has started.
+        fields.remove("stdin");
+        fields.insert("type".to_string(), "init".into());
+    }
+
+    Ok(message.to_string())
+}
+
+/// Generates the error for Piston closing the connection, if it was
+/// not closed because the job completed.
+///
+/// # Arguments
+/// - `frame` - The close frame Piston sent, if any.
+/// - `error` - The last error message Piston sent, if any.
+///
+/// # Returns
+/// - [`Option<PistonError>`] - The error, if any.
+fn close_error(frame: Option<CloseFrame<'_>>, error: Option<String>) -> Option<PistonError> {
+    let frame = frame?;
+    let code = u16::from(frame.code);
+
+    match code {
+        1000 | JOB_COMPLETED => None,
+        _ => Some(PistonError::Api {
+            status: code,
+            message: error.unwrap_or_else(|| frame.reason.to_string()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test_stream_private {
+    use super::close_error;
+    use super::init_message;
+    use super::ws_url;
+    use super::Event;
+    use super::OutputChunk;
+    use crate::Client;
+    use crate::Executor;
+    use crate::File;
+    use crate::PistonError;
+    use std::net::TcpListener;
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Accepts one WebSocket connection, sends each piece of data to
+    /// stdout, then either completes the job or hangs, and returns the
+    /// url of the server.
+    fn serve(data: &'static [&'static str], hang: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tokio_tungstenite::tungstenite::accept(stream).unwrap();
+            let _ = socket.read().unwrap();
+
+            for data in data {
+                let event = serde_json::json!({"type": "data", "stream": "stdout", "data": data});
+                socket.send(Message::Text(event.to_string())).unwrap();
+            }
+
+            if hang {
+                std::thread::sleep(Duration::from_secs(2));
+                return;
+            }
+
+            let _ = socket.close(Some(CloseFrame {
+                code: CloseCode::from(4999),
+                reason: "Job Completed".into(),
+            }));
+
+            while socket.read().is_ok() {}
+        });
+
+        url
+    }
+
+    /// A valid executor to send to the test server.
+    fn executor() -> Executor {
+        Executor::new()
+            .set_language("python")
+            .add_file(File::default().set_content("print(42)"))
+    }
+
+    /// An output chunk sent to stdout.
+    fn stdout(data: &str) -> OutputChunk {
+        OutputChunk {
+            stream: "stdout".to_string(),
+            data: data.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_ws_url() {
+        assert_eq!(
+            ws_url("https://emkc.org/api/v2/piston"),
+            "wss://emkc.org/api/v2/piston/connect"
+        );
+        assert_eq!(
+            ws_url("http://localhost:2000/api/v2"),
+            "ws://localhost:2000/api/v2/connect"
+        );
+    }
+
+    #[test]
+    fn test_init_message() {
+        let executor = Executor::new().set_language("python").set_stdin("42");
+        let message: serde_json::Value =
+            serde_json::from_str(&init_message(&executor).unwrap()).unwrap();
+
+        assert_eq!(message["type"], "init");
+        assert_eq!(message["language"], "python");
+        assert!(message.get("stdin").is_none());
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let data = r#"{"type":"data","stream":"stdout","data":"42\n"}"#;
+        let stage = r#"{"type":"stage","stage":"run"}"#;
+
+        assert!(matches!(
+            serde_json::from_str::<Event>(data).unwrap(),
+            Event::Data { stream, data } if stream == "stdout" && data == "42\n"
+        ));
+        assert!(matches!(
+            serde_json::from_str::<Event>(stage).unwrap(),
+            Event::Other
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_streaming_with_timeout() {
+        let client = Client::with_url(&serve(&["1\n", "2\n"], false));
+        let chunks = client
+            .execute_streaming_with_timeout(&executor(), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(chunks, vec![stdout("1\n"), stdout("2\n")]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_streaming_with_timeout_partial() {
+        let client = Client::with_url(&serve(&["1\n", "2\n"], true));
+        let err = client
+            .execute_streaming_with_timeout(&executor(), Duration::from_millis(300))
+            .await
+            .unwrap_err();
+
+        assert!(err.is_timeout());
+
+        match err {
+            PistonError::StreamTimeout {
+                endpoint,
+                elapsed,
+                partial,
+            } => {
+                assert!(endpoint.starts_with("ws://127.0.0.1:"));
+                assert!(elapsed >= Duration::from_millis(300));
+                assert_eq!(partial, vec![stdout("1\n"), stdout("2\n")]);
+            }
+            other => panic!("Expected a stream timeout, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_close_error() {
+        let frame = |code: u16| {
+            Some(CloseFrame {
+                code: CloseCode::from(code),
+                reason: "Notified Error".into(),
+            })
+        };
+
+        assert!(close_error(frame(4999), None).is_none());
+        assert!(close_error(None, None).is_none());
+
+        match close_error(frame(4002), Some("Oops".to_string())) {
+            Some(PistonError::Api { status, message }) => {
+                assert_eq!(status, 4002);
+                assert_eq!(message, "Oops");
+            }
+            _ => panic!("Expected an api error"),
+        }
+    }
+}
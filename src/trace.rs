@@ -0,0 +1,81 @@
+//! `tracing` instrumentation for requests to Piston.
+//!
+//! Requires the `tracing` feature to be enabled.
+
+use std::future::Future;
+use std::time::Instant;
+
+use tracing::{Instrument, Span};
+
+use super::PistonError;
+
+/// Runs a request inside the given span, then emits an event with the
+/// status and elapsed time. Failed requests are logged at `warn`.
+///
+/// # Arguments
+/// - `request` - The request to run.
+/// - `span` - The span to run it in.
+///
+/// # Returns
+/// - [`Result<T, PistonError>`] - The result of the request.
+pub(crate) async fn instrument<T, F>(request: F, span: Span) -> Result<T, PistonError>
+where
+    F: Future<Output = Result<T, PistonError>>,
+{
+    async move {
+        let started = Instant::now();
+        let result = request.await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+
+        match &result {
+            Ok(_) => tracing::debug!(status = 200, elapsed_ms, "Piston request succeeded"),
+            Err(e) => tracing::warn!(
+                status = status_of(e),
+                elapsed_ms,
+                error = %e,
+                "Piston request failed"
+            ),
+        }
+
+        result
+    }
+    .instrument(span)
+    .await
+}
+
+/// The status code Piston responded with, if the error came from a
+/// response.
+///
+/// # Arguments
+/// - `error` - The error to get the status of.
+///
+/// # Returns
+/// - [`Option<u16>`] - The status code, if any.
+fn status_of(error: &PistonError) -> Option<u16> {
+    match error {
+        PistonError::Api { status, .. } => Some(*status),
+        PistonError::RateLimited { .. } => Some(429),
+        PistonError::Http(e) => e.status().map(|s| s.as_u16()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_trace_private {
+    use super::status_of;
+    use crate::PistonError;
+
+    #[test]
+    fn test_status_of() {
+        let api = PistonError::Api {
+            status: 400,
+            message: String::new(),
+        };
+        let limited = PistonError::RateLimited { retry_after: None };
+        let invalid = PistonError::InvalidExecutor(String::new());
+
+        assert_eq!(status_of(&api), Some(400));
+        assert_eq!(status_of(&limited), Some(429));
+        assert_eq!(status_of(&invalid), None);
+    }
+}